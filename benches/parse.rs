@@ -0,0 +1,81 @@
+//! Hand-written parser against the PEM grammar
+//!
+//! Run with `cargo bench --features bench`. The `fast` path is the default
+//! one, `grammar` forces the pest grammar with the hidden
+//! `PemBlocks::grammar_only`.
+
+extern crate easypem;
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use easypem::error::PemResult;
+use easypem::{PemBlocks, PemMessage};
+use std::fs;
+use std::path::PathBuf;
+
+/// Number of blocks of the synthetic bundle, about the size of the
+/// Mozilla CA bundle
+const BUNDLE_BLOCKS: usize = 150;
+
+/// Parse every block of `text` with the given path
+fn parse_all(text: &str, grammar_only: bool) -> PemResult<Vec<PemMessage>> {
+    let blocks = PemBlocks::new(text);
+    match grammar_only {
+        false => blocks.collect(),
+        true => blocks.grammar_only().collect(),
+    }
+}
+
+const PATHS: [(&str, bool); 2] = [("fast", false), ("grammar", true)];
+
+/// Certificate bundle with a comment before each block, like the ones
+/// shipped by operating systems
+fn synthetic_bundle() -> String {
+    (0..BUNDLE_BLOCKS)
+        .map(|idx| {
+            let content = (0..1400).map(|byte| (byte * 31 + idx) as u8);
+            let pem = PemMessage::new("CERTIFICATE", content.collect::<Vec<_>>());
+            format!(
+                "# Issuer: CN=Example Root CA {}\n# Serial: {}\n{}\n\n",
+                idx, idx, pem
+            )
+        })
+        .collect()
+}
+
+fn assets(c: &mut Criterion) {
+    let mut dir = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    dir.push("tests/assets");
+    let mut files = fs::read_dir(dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .collect::<Vec<_>>();
+    files.sort();
+
+    let mut group = c.benchmark_group("assets");
+    for file in &files {
+        let text = fs::read_to_string(file).unwrap();
+        let name = file.file_stem().unwrap().to_string_lossy();
+        group.throughput(Throughput::Bytes(text.len() as u64));
+        for &(path, grammar_only) in &PATHS {
+            group.bench_with_input(BenchmarkId::new(path, &name), &text, |b, text| {
+                b.iter(|| parse_all(black_box(text), grammar_only).unwrap())
+            });
+        }
+    }
+    group.finish();
+}
+
+fn bundle(c: &mut Criterion) {
+    let text = synthetic_bundle();
+    let mut group = c.benchmark_group("bundle");
+    group.throughput(Throughput::Bytes(text.len() as u64));
+    for &(path, grammar_only) in &PATHS {
+        group.bench_function(path, |b| {
+            b.iter(|| parse_all(black_box(&text), grammar_only).unwrap())
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, assets, bundle);
+criterion_main!(benches);
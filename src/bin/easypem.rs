@@ -0,0 +1,236 @@
+//! Command-line tool to split, join, convert and inspect PEM files
+
+extern crate easypem;
+
+use easypem::{EncodeOptions, PemLabel, PemMessage};
+use sha2::{Digest, Sha256};
+use std::error::Error;
+use std::fs;
+use std::io::{self, Read, Write};
+use std::process;
+
+const USAGE: &str = "Usage: easypem <command> [options]
+
+Commands:
+  split [INPUT] [--prefix PREFIX]
+      Write each block to its own file, PREFIX1.pem, PREFIX2.pem, ...
+      (default prefix: block-)
+  join FILE... [-o OUTPUT]
+      Write the blocks of every file, in order, to a single bundle
+  to-der [INPUT] [-o OUTPUT] [--block N]
+      Write the DER content of a block (default: the first one)
+  from-der [INPUT] [-o OUTPUT] [--label LABEL]
+      Wrap DER in a PEM block, guessing the label if it is not given
+  inspect [INPUT]
+      Print the label, headers, content length and SHA-256 fingerprint
+      of each block
+  relabel LABEL [INPUT] [-o OUTPUT] [--block N]
+      Change the label of a block, or of every block if N is not given
+
+INPUT defaults to the standard input and OUTPUT to the standard output.
+Blocks are numbered from 1.";
+
+type CliResult<T> = Result<T, Box<dyn Error>>;
+
+/// Command-line arguments of a subcommand
+#[derive(Debug, Default)]
+struct Args {
+    positional: Vec<String>,
+    output: Option<String>,
+    prefix: Option<String>,
+    label: Option<String>,
+    block: Option<usize>,
+}
+
+impl Args {
+    /// Parse the arguments of `command`, which accepts the `options`
+    fn parse<I: Iterator<Item = String>>(
+        command: &str,
+        options: &[&str],
+        mut args: I,
+    ) -> CliResult<Self> {
+        let mut parsed = Args::default();
+        while let Some(arg) = args.next() {
+            if arg.starts_with('-') && arg != "-" && !options.contains(&arg.as_str()) {
+                return Err(format!("Unknown option {} for {}", arg, command).into());
+            }
+            let mut value = || {
+                args.next()
+                    .ok_or_else(|| format!("Missing value for {}", arg))
+            };
+            match arg.as_str() {
+                "-o" | "--output" => parsed.output = Some(value()?),
+                "--prefix" => parsed.prefix = Some(value()?),
+                "--label" => parsed.label = Some(value()?),
+                "--block" => {
+                    let block = value()?
+                        .parse::<usize>()
+                        .ok()
+                        .filter(|&n| n > 0)
+                        .ok_or("The block number must be a positive integer")?;
+                    parsed.block = Some(block);
+                }
+                _ => parsed.positional.push(arg),
+            }
+        }
+        Ok(parsed)
+    }
+
+    /// The single optional input file, after `skip` other positional arguments
+    fn input(&self, skip: usize) -> CliResult<Option<&str>> {
+        match &self.positional[skip.min(self.positional.len())..] {
+            [] => Ok(None),
+            [input] => Ok(Some(input)),
+            _ => Err("Too many arguments".into()),
+        }
+    }
+}
+
+/// Read a file, or the standard input if `path` is `None` or `-`
+fn read_input(path: Option<&str>) -> CliResult<Vec<u8>> {
+    match path {
+        None | Some("-") => {
+            let mut data = Vec::new();
+            io::stdin().read_to_end(&mut data)?;
+            Ok(data)
+        }
+        Some(path) => Ok(fs::read(path).map_err(|err| format!("{}: {}", path, err))?),
+    }
+}
+
+/// Write a file, or the standard output if `path` is `None` or `-`
+fn write_output(path: Option<&str>, data: &[u8]) -> CliResult<()> {
+    match path {
+        None | Some("-") => Ok(io::stdout().write_all(data)?),
+        Some(path) => Ok(fs::write(path, data).map_err(|err| format!("{}: {}", path, err))?),
+    }
+}
+
+fn read_blocks(path: Option<&str>) -> CliResult<Vec<PemMessage>> {
+    let blocks = PemMessage::parse_all_bytes(&read_input(path)?)?;
+    if blocks.is_empty() {
+        return Err(format!("{}: no PEM block found", path.unwrap_or("<stdin>")).into());
+    }
+    Ok(blocks)
+}
+
+/// Blocks as a bundle, each block followed by a line ending
+fn bundle(blocks: &[PemMessage]) -> String {
+    blocks.iter().map(|pem| format!("{}\n", pem)).collect()
+}
+
+fn select(blocks: &[PemMessage], block: Option<usize>) -> CliResult<&PemMessage> {
+    let index = block.unwrap_or(1);
+    blocks
+        .get(index - 1)
+        .ok_or_else(|| format!("No block {}, found {}", index, blocks.len()).into())
+}
+
+fn split(args: &Args) -> CliResult<()> {
+    let blocks = read_blocks(args.input(0)?)?;
+    let prefix = args.prefix.as_deref().unwrap_or("block-");
+    for (index, pem) in blocks.iter().enumerate() {
+        let path = format!("{}{}.pem", prefix, index + 1);
+        write_output(Some(&path), format!("{}\n", pem).as_bytes())?;
+        println!("{}", path);
+    }
+    Ok(())
+}
+
+fn join(args: &Args) -> CliResult<()> {
+    if args.positional.is_empty() {
+        return Err("Missing input files".into());
+    }
+    let mut blocks = Vec::new();
+    for path in &args.positional {
+        blocks.extend(read_blocks(Some(path))?);
+    }
+    write_output(args.output.as_deref(), bundle(&blocks).as_bytes())
+}
+
+fn to_der(args: &Args) -> CliResult<()> {
+    let blocks = read_blocks(args.input(0)?)?;
+    let pem = select(&blocks, args.block)?;
+    write_output(args.output.as_deref(), pem.to_der()?)
+}
+
+fn from_der(args: &Args) -> CliResult<()> {
+    let der = read_input(args.input(0)?)?;
+    let label = match &args.label {
+        Some(label) => label.clone(),
+        None => PemLabel::infer(&der)
+            .ok_or("Unable to guess the label, use --label")?
+            .to_string(),
+    };
+    let pem = PemMessage::from_der(&label, der)?;
+    write_output(args.output.as_deref(), format!("{}\n", pem).as_bytes())
+}
+
+fn inspect(args: &Args) -> CliResult<()> {
+    let blocks = read_blocks(args.input(0)?)?;
+    let mut out = String::new();
+    for (index, pem) in blocks.iter().enumerate() {
+        if index > 0 {
+            out.push('\n');
+        }
+        out.push_str(&format!("Block {}\n", index + 1));
+        out.push_str(&format!("  Label: {}\n", pem.label));
+        for field in &pem.headers.fields {
+            let value = field.value.replace('\n', " ");
+            out.push_str(&format!("  Header: {}: {}\n", field.name, value.trim()));
+        }
+        out.push_str(&format!("  Length: {} bytes\n", pem.content.len()));
+        let fingerprint = Sha256::digest(&pem.content)
+            .iter()
+            .map(|b| format!("{:02X}", b))
+            .collect::<Vec<_>>()
+            .join(":");
+        out.push_str(&format!("  SHA-256: {}\n", fingerprint));
+    }
+    write_output(None, out.as_bytes())
+}
+
+fn relabel(args: &Args) -> CliResult<()> {
+    let label = args.positional.first().ok_or("Missing label")?;
+    let mut blocks = read_blocks(args.input(1)?)?;
+    if args.block.is_some() {
+        select(&blocks, args.block)?;
+    }
+    for (index, pem) in blocks.iter_mut().enumerate() {
+        if args.block.is_none() || args.block == Some(index + 1) {
+            pem.label = label.clone();
+            pem.encode_with(&EncodeOptions::default())?;
+        }
+    }
+    write_output(args.output.as_deref(), bundle(&blocks).as_bytes())
+}
+
+fn run() -> CliResult<()> {
+    let mut args = std::env::args().skip(1);
+    let command = args.next();
+    let command = match command.as_deref() {
+        None | Some("-h") | Some("--help") | Some("help") => {
+            println!("{}", USAGE);
+            return Ok(());
+        }
+        Some(command) => command.to_owned(),
+    };
+    type Command = fn(&Args) -> CliResult<()>;
+    let (run, options): (Command, &[&str]) = match command.as_str() {
+        "split" => (split, &["--prefix"]),
+        "join" => (join, &["-o", "--output"]),
+        "to-der" => (to_der, &["-o", "--output", "--block"]),
+        "from-der" => (from_der, &["-o", "--output", "--label"]),
+        "inspect" => (inspect, &[]),
+        "relabel" => (relabel, &["-o", "--output", "--block"]),
+        _ => return Err(format!("Unknown command {}\n\n{}", command, USAGE).into()),
+    };
+    run(&Args::parse(&command, options, args)?)
+}
+
+fn main() {
+    if let Err(err) = run() {
+        eprintln!("easypem: {}", err);
+        process::exit(1);
+    }
+}
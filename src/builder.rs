@@ -1,40 +1,164 @@
-use crate::headers::PemHeader;
-use crate::PemMessage;
+use crate::error::{Error, ErrorKind, PemResult};
+use crate::headers::{
+    ContentDomain, DEKInfo, HeaderField, PemHeader, ProcType, CONTENT_DOMAIN, DEK_INFO, PROC_TYPE,
+};
+use crate::parser::validate_label;
+use crate::{BlockSpans, PemMessage, Source, SourceLayout};
+use std::io::Read;
 
-#[derive(Debug, Default)]
-pub struct PemBuilder<'p> {
-    label: Option<&'p str>,
-    headers: Option<PemHeader>,
+/// Builder of a [`PemMessage`]
+///
+/// The label is checked against the PEM grammar, and the header fields,
+/// typed or added with [`header`](PemBuilder::header), are parsed as they
+/// would be in a PEM text, so the built message can always be written back.
+///
+/// ```
+/// # use easypem::{PemBuilder, headers::{ProcType, ProcTypeSpecifier}};
+/// let pem = PemBuilder::new()
+///     .label("RSA PRIVATE KEY")
+///     .proc_type(ProcType(4, ProcTypeSpecifier::ENCRYPTED))
+///     .header("DEK-Info", "AES-128-CBC,000102030405060708090A0B0C0D0E0F")
+///     .content(b"not really a key".to_vec())
+///     .build()
+///     .unwrap();
+///
+/// assert_eq!(pem.headers.dek_info.unwrap().algorithm, "AES-128-CBC");
+/// assert!(PemBuilder::new().label("BAD--LABEL").build().is_err());
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct PemBuilder {
+    label: Option<String>,
+    headers: PemHeader,
+    fields: Vec<HeaderField>,
     content: Vec<u8>,
+    source: Source,
 }
 
-impl<'p> PemBuilder<'p> {
-    pub fn label(&mut self, label_str: &'p str) -> &mut Self {
-        self.label = Some(label_str);
+impl PemBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn label<L: Into<String>>(mut self, label: L) -> Self {
+        self.label = Some(label.into());
+        self
+    }
+
+    /// Replace every header field set so far
+    pub fn headers(mut self, headers: PemHeader) -> Self {
+        self.headers = headers;
+        self.fields.clear();
+        self
+    }
+
+    pub fn proc_type(mut self, proc_type: ProcType) -> Self {
+        self.headers.proc_type = Some(proc_type);
         self
     }
 
-    pub fn headers(&mut self, headers: PemHeader) -> &mut Self {
-        self.headers = Some(headers);
+    pub fn content_domain(mut self, content_domain: ContentDomain) -> Self {
+        self.headers.content_domain = Some(content_domain);
         self
     }
 
-    pub fn content(&mut self, data: Vec<u8>) -> &mut Self {
-        self.content = data;
+    pub fn dek_info(mut self, dek_info: DEKInfo) -> Self {
+        self.headers.dek_info = Some(dek_info);
         self
     }
 
-    pub fn build(self) -> PemMessage {
-        let label = if let Some(s) = self.label {
-            s.to_owned()
-        } else {
-            String::new()
-        };
-        let headers = self.headers.unwrap_or_default();
+    /// Add a header field after the ones set so far
+    ///
+    /// Continuation lines of `value` must each be preceded by `\n` and start
+    /// with whitespace. Standard fields also set their typed counterpart.
+    pub fn header<N: Into<String>, V: Into<String>>(mut self, name: N, value: V) -> Self {
+        self.fields.push(HeaderField::new(name, value));
+        self
+    }
+
+    pub fn content<C: Into<Vec<u8>>>(mut self, content: C) -> Self {
+        self.content = content.into();
+        self
+    }
+
+    /// Read the whole content from `reader`
+    pub fn content_from_reader<R: Read>(mut self, mut reader: R) -> PemResult<Self> {
+        self.content.clear();
+        reader.read_to_end(&mut self.content)?;
+        Ok(self)
+    }
+
+    pub fn explanatory_text<T: Into<String>>(mut self, text: T) -> Self {
+        self.source.explanatory_text = text.into();
+        self
+    }
+
+    pub(crate) fn layout(mut self, layout: SourceLayout) -> Self {
+        self.source.layout = Some(layout);
+        self
+    }
+
+    pub(crate) fn spans(mut self, spans: BlockSpans) -> Self {
+        self.source.spans = Some(spans);
+        self
+    }
+
+    /// Build the message
+    ///
+    /// # Errors
+    ///
+    /// Fails with [`ErrorKind::InvalidLabel`] if the label is missing or
+    /// malformed, with [`ErrorKind::InvalidHeader`] if the header fields
+    /// would not be parsed back or if a standard field is set twice, and with
+    /// [`ErrorKind::MissingContent`] if both the content and the header are
+    /// empty.
+    pub fn build(mut self) -> PemResult<PemMessage> {
+        let label = self
+            .label
+            .as_ref()
+            .ok_or_else(|| Error::new(ErrorKind::InvalidLabel, "Missing label"))?;
+        validate_label(label)?;
+        let mut text = self.headers.to_string();
+        for field in &self.fields {
+            text.push_str(&format!("{}\n", field));
+        }
+        if !text.is_empty() {
+            self.headers = PemHeader::from_str(&text).map_err(|err| {
+                Error::new(
+                    ErrorKind::InvalidHeader,
+                    format!("Invalid header fields: {}", err.variant.message()),
+                )
+                .with_source(err)
+            })?;
+            // The spans point into `text`, not into any source
+            for field in &mut self.headers.fields {
+                field.span = None;
+            }
+        }
+        for (idx, field) in self.headers.fields.iter().enumerate() {
+            let name = field.name.as_str();
+            if [PROC_TYPE, CONTENT_DOMAIN, DEK_INFO].contains(&name)
+                && self.headers.fields[..idx].iter().any(|f| f.name == name)
+            {
+                return Err(Error::new(
+                    ErrorKind::InvalidHeader,
+                    format!("Duplicate header field \"{}\"", name),
+                ));
+            }
+        }
+        // RFC 1424 messages have headers and no content
+        if self.content.is_empty() && self.headers.is_empty() {
+            return Err(Error::new(ErrorKind::MissingContent, "Missing content"));
+        }
+        Ok(self.build_parsed())
+    }
+
+    /// Build a message whose label and headers come from the parser
+    pub(crate) fn build_parsed(self) -> PemMessage {
         PemMessage {
-            label,
-            headers,
+            label: self.label.unwrap_or_default(),
+            headers: self.headers,
             content: self.content,
+            source: self.source,
         }
     }
 }
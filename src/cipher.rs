@@ -0,0 +1,107 @@
+use crate::error::{Error, ErrorKind};
+use aes::{Aes128, Aes192, Aes256};
+use cbc::cipher::block_padding::Pkcs7;
+use cbc::cipher::{BlockCipher, BlockDecryptMut, BlockEncryptMut, KeyInit, KeyIvInit};
+use des::{Des, TdesEde3};
+use std::fmt::{Display, Formatter, Result as FmtResult};
+use std::str::FromStr;
+
+/// Block ciphers in CBC mode with PKCS#7 padding
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Cipher {
+    DesCbc,
+    DesEde3Cbc,
+    Aes128Cbc,
+    Aes192Cbc,
+    Aes256Cbc,
+}
+
+impl Cipher {
+    /// Length of the derived key in bytes
+    pub fn key_len(self) -> usize {
+        match self {
+            Cipher::DesCbc => 8,
+            Cipher::DesEde3Cbc => 24,
+            Cipher::Aes128Cbc => 16,
+            Cipher::Aes192Cbc => 24,
+            Cipher::Aes256Cbc => 32,
+        }
+    }
+
+    /// Length of the IV, which is also the block size, in bytes
+    pub fn iv_len(self) -> usize {
+        match self {
+            Cipher::DesCbc | Cipher::DesEde3Cbc => 8,
+            Cipher::Aes128Cbc | Cipher::Aes192Cbc | Cipher::Aes256Cbc => 16,
+        }
+    }
+
+    pub(crate) fn decrypt(self, key: &[u8], iv: &[u8], data: &[u8]) -> Option<Vec<u8>> {
+        match self {
+            Cipher::DesCbc => cbc_decrypt::<Des>(key, iv, data),
+            Cipher::DesEde3Cbc => cbc_decrypt::<TdesEde3>(key, iv, data),
+            Cipher::Aes128Cbc => cbc_decrypt::<Aes128>(key, iv, data),
+            Cipher::Aes192Cbc => cbc_decrypt::<Aes192>(key, iv, data),
+            Cipher::Aes256Cbc => cbc_decrypt::<Aes256>(key, iv, data),
+        }
+    }
+
+    pub(crate) fn encrypt(self, key: &[u8], iv: &[u8], data: &[u8]) -> Vec<u8> {
+        match self {
+            Cipher::DesCbc => cbc_encrypt::<Des>(key, iv, data),
+            Cipher::DesEde3Cbc => cbc_encrypt::<TdesEde3>(key, iv, data),
+            Cipher::Aes128Cbc => cbc_encrypt::<Aes128>(key, iv, data),
+            Cipher::Aes192Cbc => cbc_encrypt::<Aes192>(key, iv, data),
+            Cipher::Aes256Cbc => cbc_encrypt::<Aes256>(key, iv, data),
+        }
+    }
+}
+
+impl FromStr for Cipher {
+    type Err = ();
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "DES-CBC" => Ok(Cipher::DesCbc),
+            "DES-EDE3-CBC" => Ok(Cipher::DesEde3Cbc),
+            "AES-128-CBC" => Ok(Cipher::Aes128Cbc),
+            "AES-192-CBC" => Ok(Cipher::Aes192Cbc),
+            "AES-256-CBC" => Ok(Cipher::Aes256Cbc),
+            _ => Err(()),
+        }
+    }
+}
+
+impl Display for Cipher {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        match self {
+            Cipher::DesCbc => write!(f, "DES-CBC"),
+            Cipher::DesEde3Cbc => write!(f, "DES-EDE3-CBC"),
+            Cipher::Aes128Cbc => write!(f, "AES-128-CBC"),
+            Cipher::Aes192Cbc => write!(f, "AES-192-CBC"),
+            Cipher::Aes256Cbc => write!(f, "AES-256-CBC"),
+        }
+    }
+}
+
+fn cbc_decrypt<C>(key: &[u8], iv: &[u8], data: &[u8]) -> Option<Vec<u8>>
+where
+    C: BlockDecryptMut + BlockCipher + KeyInit,
+{
+    cbc::Decryptor::<C>::new_from_slices(key, iv)
+        .ok()?
+        .decrypt_padded_vec_mut::<Pkcs7>(data)
+        .ok()
+}
+
+fn cbc_encrypt<C>(key: &[u8], iv: &[u8], data: &[u8]) -> Vec<u8>
+where
+    C: BlockEncryptMut + BlockCipher + KeyInit,
+{
+    cbc::Encryptor::<C>::new_from_slices(key, iv)
+        .unwrap()
+        .encrypt_padded_vec_mut::<Pkcs7>(data)
+}
+
+pub(crate) fn crypto_err<S: Into<String>>(message: S) -> Error {
+    Error::new(ErrorKind::Crypto, message)
+}
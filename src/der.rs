@@ -0,0 +1,131 @@
+//! Minimal DER reader and writer
+//!
+//! Only definite lengths and single-byte tags are supported,
+//! which is enough for the structures handled by this crate.
+
+pub(crate) const INTEGER: u8 = 0x02;
+pub(crate) const BIT_STRING: u8 = 0x03;
+pub(crate) const OCTET_STRING: u8 = 0x04;
+#[cfg(feature = "pkcs8")]
+pub(crate) const NULL: u8 = 0x05;
+pub(crate) const OID: u8 = 0x06;
+pub(crate) const UTC_TIME: u8 = 0x17;
+pub(crate) const GENERALIZED_TIME: u8 = 0x18;
+pub(crate) const SEQUENCE: u8 = 0x30;
+/// `[0]`, constructed
+pub(crate) const CONTEXT_0: u8 = 0xA0;
+
+/// Sequential reader over DER encoded elements
+#[derive(Debug, Clone)]
+pub(crate) struct DerReader<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> DerReader<'a> {
+    pub fn new(data: &'a [u8]) -> Self {
+        DerReader { data }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    pub fn peek_tag(&self) -> Option<u8> {
+        self.data.first().copied()
+    }
+
+    /// Read the next element, returning its tag and its content
+    pub fn read_any(&mut self) -> Option<(u8, &'a [u8])> {
+        let (&tag, rest) = self.data.split_first()?;
+        let (&first, rest) = rest.split_first()?;
+        let (len, rest) = if first < 0x80 {
+            (first as usize, rest)
+        } else {
+            let count = (first & 0x7F) as usize;
+            if count == 0 || count > 4 || rest.len() < count {
+                return None;
+            }
+            let len = rest[..count]
+                .iter()
+                .fold(0usize, |len, &b| (len << 8) | b as usize);
+            (len, &rest[count..])
+        };
+        if rest.len() < len {
+            return None;
+        }
+        self.data = &rest[len..];
+        Some((tag, &rest[..len]))
+    }
+
+    /// Read the next element if it has the given tag
+    ///
+    /// Nothing is consumed if the tag does not match.
+    pub fn read(&mut self, tag: u8) -> Option<&'a [u8]> {
+        if self.peek_tag()? != tag {
+            return None;
+        }
+        self.read_any().map(|(_, content)| content)
+    }
+
+    /// Tags of the remaining elements, or `None` if they are malformed
+    pub fn tags(mut self) -> Option<Vec<u8>> {
+        let mut tags = Vec::new();
+        while !self.is_empty() {
+            tags.push(self.read_any()?.0);
+        }
+        Some(tags)
+    }
+
+    /// Read a non-negative INTEGER fitting in a `u64`
+    #[cfg(feature = "pkcs8")]
+    pub fn read_uint(&mut self) -> Option<u64> {
+        let content = self.read(INTEGER)?;
+        if content.is_empty() || content[0] & 0x80 != 0 {
+            return None;
+        }
+        let content = if content[0] == 0 {
+            &content[1..]
+        } else {
+            content
+        };
+        if content.len() > 8 {
+            return None;
+        }
+        Some(content.iter().fold(0, |n, &b| (n << 8) | b as u64))
+    }
+}
+
+/// Check that `data` is made of a single DER element
+pub(crate) fn is_single_element(data: &[u8]) -> bool {
+    let mut reader = DerReader::new(data);
+    reader.read_any().is_some() && reader.is_empty()
+}
+
+/// Append a DER element to `out`
+#[cfg(feature = "pkcs8")]
+pub(crate) fn write_tlv(out: &mut Vec<u8>, tag: u8, content: &[u8]) {
+    out.push(tag);
+    let len = content.len();
+    if len < 0x80 {
+        out.push(len as u8);
+    } else {
+        let bytes = len.to_be_bytes();
+        let skip = bytes.iter().take_while(|&&b| b == 0).count();
+        out.push(0x80 | (bytes.len() - skip) as u8);
+        out.extend_from_slice(&bytes[skip..]);
+    }
+    out.extend_from_slice(content);
+}
+
+/// Append a non-negative INTEGER to `out`
+#[cfg(feature = "pkcs8")]
+pub(crate) fn write_uint(out: &mut Vec<u8>, n: u64) {
+    let bytes = n.to_be_bytes();
+    let skip = bytes[..7].iter().take_while(|&&b| b == 0).count();
+    let mut content = Vec::with_capacity(9);
+    if bytes[skip] & 0x80 != 0 {
+        content.push(0);
+    }
+    content.extend_from_slice(&bytes[skip..]);
+    write_tlv(out, INTEGER, &content);
+}
@@ -1,36 +1,247 @@
 use crate::headers::Rule as HeadersRule;
-use crate::parser::Rule as PemRule;
-use failure::Fail;
-use pest::error::Error as PestError;
+use pest::error::{Error as PestError, ErrorVariant, InputLocation, LineColLocation};
+use std::error::Error as StdError;
 use std::fmt;
+use std::io;
 
 pub type PemResult<T> = Result<T, Error>;
 
+/// Category of an [`Error`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum ErrorKind {
+    /// No `-----BEGIN` line was found
+    MissingBeginBoundary,
+    /// A block has no `-----END` line
+    MissingEndBoundary,
+    /// The label of the `-----END` line differs from the `-----BEGIN` one
+    LabelMismatch,
+    /// The label of an encapsulation boundary is malformed
+    InvalidLabel,
+    /// The base64 text is malformed
+    InvalidBase64,
+    /// A block has no base64 text
+    MissingContent,
+    /// A header field is malformed
+    InvalidHeader,
+    /// A non-ASCII byte was found inside a block
+    NonAscii,
+    /// The content is not a single DER element
+    InvalidDer,
+    /// The `Proc-Type` header field has an unknown type
+    UnsupportedProcType,
+    /// The input does not follow the PEM grammar in some other way
+    Syntax,
+    /// Failure of the underlying reader or writer
+    Io,
+    /// Failure while encrypting or decrypting the content
+    Crypto,
+    /// Decrypting would exceed the configured resource limits
+    LimitExceeded,
+}
+
+impl fmt::Display for ErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            ErrorKind::MissingBeginBoundary => "missing pre-encapsulation boundary",
+            ErrorKind::MissingEndBoundary => "missing post-encapsulation boundary",
+            ErrorKind::LabelMismatch => "label mismatch",
+            ErrorKind::InvalidLabel => "invalid label",
+            ErrorKind::InvalidBase64 => "invalid base64",
+            ErrorKind::MissingContent => "missing content",
+            ErrorKind::InvalidHeader => "invalid header",
+            ErrorKind::NonAscii => "non-ASCII byte",
+            ErrorKind::InvalidDer => "invalid DER",
+            ErrorKind::UnsupportedProcType => "unsupported Proc-Type",
+            ErrorKind::Syntax => "syntax error",
+            ErrorKind::Io => "I/O error",
+            ErrorKind::Crypto => "cryptographic error",
+            ErrorKind::LimitExceeded => "resource limit exceeded",
+        })
+    }
+}
+
+/// Location of an error in the input
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Position {
+    /// 1-based line number
+    pub line: usize,
+    /// 1-based column, in characters
+    pub column: usize,
+    /// Byte offset from the start of the input
+    pub offset: usize,
+}
+
+impl Position {
+    /// Compute the line and column of `offset` in `input`
+    pub(crate) fn from_offset(input: &str, offset: usize) -> Self {
+        let before = &input[..offset];
+        let (line, line_start) =
+            line_starts(before).fold((1, 0), |(line, _), start| (line + 1, start));
+        Position {
+            line,
+            column: before[line_start..].chars().count() + 1,
+            offset,
+        }
+    }
+
+    /// Move a position found in an input starting at `base` onto the whole
+    /// input
+    pub(crate) fn rebase(self, base: Position) -> Self {
+        Position {
+            line: base.line + self.line - 1,
+            column: match self.line {
+                1 => base.column + self.column - 1,
+                _ => self.column,
+            },
+            offset: base.offset + self.offset,
+        }
+    }
+}
+
+/// Offsets of the lines of `input` but the first one
+///
+/// Like the `NEWLINE` of the grammar, `\r\n`, `\n` and a lone `\r` each
+/// end a line.
+pub(crate) fn line_starts(input: &str) -> impl Iterator<Item = usize> + '_ {
+    let bytes = input.as_bytes();
+    bytes
+        .iter()
+        .enumerate()
+        .filter_map(move |(idx, &b)| match b {
+            b'\n' => Some(idx + 1),
+            b'\r' if bytes.get(idx + 1) != Some(&b'\n') => Some(idx + 1),
+            _ => None,
+        })
+}
+
+impl fmt::Display for Position {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "line {}, column {}", self.line, self.column)
+    }
+}
+
+/// Error of every fallible operation of this crate
+///
+/// ```
+/// # use easypem::{PemMessage, error::ErrorKind};
+/// let text = "-----BEGIN MESSAGE-----
+/// Zmlyc3Q=
+/// -----END OTHER-----";
+///
+/// let err = text.parse::<PemMessage>().unwrap_err();
+/// assert_eq!(err.kind(), ErrorKind::LabelMismatch);
+/// assert_eq!(err.position().unwrap().line, 3);
+/// ```
 #[derive(Debug)]
-pub enum Error {
-    PemParserError(PestError<PemRule>),
-    HeaderParserError(PestError<HeadersRule>),
+pub struct Error {
+    kind: ErrorKind,
+    message: String,
+    position: Option<Position>,
+    source: Option<Box<dyn StdError + Send + Sync>>,
+}
+
+impl Error {
+    pub(crate) fn new<S: Into<String>>(kind: ErrorKind, message: S) -> Self {
+        Error {
+            kind,
+            message: message.into(),
+            position: None,
+            source: None,
+        }
+    }
+
+    pub(crate) fn at(mut self, position: Position) -> Self {
+        self.position = Some(position);
+        self
+    }
+
+    pub(crate) fn with_source<E: StdError + Send + Sync + 'static>(mut self, source: E) -> Self {
+        self.source = Some(Box::new(source));
+        self
+    }
+
+    /// Move the position of an error found in `input[base..]` onto `input`
+    pub(crate) fn rebase(mut self, input: &str, base: usize) -> Self {
+        if let Some(position) = self.position {
+            self.position = Some(Position::from_offset(input, base + position.offset));
+        }
+        self
+    }
+
+    pub fn kind(&self) -> ErrorKind {
+        self.kind
+    }
+
+    /// Location of the error in the parsed input, if relevant
+    pub fn position(&self) -> Option<Position> {
+        self.position
+    }
+
+    /// Description of the error, without its position
+    pub fn message(&self) -> &str {
+        &self.message
+    }
 }
 
 impl fmt::Display for Error {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        match self {
-            Error::PemParserError(err) => err.fmt(f),
-            Error::HeaderParserError(err) => err.fmt(f),
+        match self.position {
+            Some(position) => write!(f, "{}: {}", position, &self.message),
+            None => f.write_str(&self.message),
         }
     }
 }
 
-impl Fail for Error {}
+impl StdError for Error {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        self.source
+            .as_ref()
+            .map(|source| source.as_ref() as &(dyn StdError + 'static))
+    }
+}
 
-impl From<PestError<PemRule>> for Error {
-    fn from(err: PestError<PemRule>) -> Self {
-        Error::PemParserError(err)
+/// Position of a pest error, relative to the parsed input
+pub(crate) fn pest_position<R: pest::RuleType>(err: &PestError<R>) -> Position {
+    let (line, column) = match err.line_col {
+        LineColLocation::Pos(pos) | LineColLocation::Span(pos, _) => pos,
+    };
+    let offset = match err.location {
+        InputLocation::Pos(offset) | InputLocation::Span((offset, _)) => offset,
+    };
+    Position {
+        line,
+        column,
+        offset,
+    }
+}
+
+/// Message of a pest error, without its position
+pub(crate) fn pest_message<R: pest::RuleType>(err: &PestError<R>) -> String {
+    match &err.variant {
+        ErrorVariant::CustomError { message } => message.clone(),
+        variant => variant.message().into_owned(),
     }
 }
 
 impl From<PestError<HeadersRule>> for Error {
     fn from(err: PestError<HeadersRule>) -> Self {
-        Error::HeaderParserError(err)
+        let kind = match &err.variant {
+            ErrorVariant::ParsingError { positives, .. }
+                if positives.contains(&HeadersRule::pemtypes) =>
+            {
+                ErrorKind::UnsupportedProcType
+            }
+            _ => ErrorKind::InvalidHeader,
+        };
+        Error::new(kind, pest_message(&err))
+            .at(pest_position(&err))
+            .with_source(err)
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(err: io::Error) -> Self {
+        Error::new(ErrorKind::Io, err.to_string()).with_source(err)
     }
 }
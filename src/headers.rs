@@ -1,4 +1,6 @@
-use crate::parser::pest_err_span;
+use crate::error::Position;
+use crate::parser::{pest_err_span, rfc1421_base64_decode};
+use crate::SourceSpan;
 use pest::error::Error;
 use pest::iterators::*;
 use pest::Parser;
@@ -6,17 +8,39 @@ use std::fmt::{Display, Formatter, Result as FmtResult};
 use std::str::FromStr;
 
 /// Struct to store standard PEM header
+///
+/// Every header field is kept in [`fields`](PemHeader::fields), in order,
+/// while the standard ones are also parsed into typed fields.
+/// When formatting, a field of `fields` which has a typed counterpart is
+/// replaced by the typed value if they differ, and typed values without a
+/// counterpart are written first.
+///
+/// The RFC 1421 originator and recipient fields, and the RFC 1424 CRL and
+/// issuer fields, are handled as a whole: unless
+/// [`originator`](PemHeader::originator), [`recipients`](PemHeader::recipients),
+/// [`crls`](PemHeader::crls) and [`issuers`](PemHeader::issuers) match the
+/// ones found in `fields`, up to whitespace, they are written in place of the
+/// first of these fields, or last if there is none.
 #[derive(Debug, Default, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
 pub struct PemHeader {
     pub proc_type: Option<ProcType>,
     pub content_domain: Option<ContentDomain>,
     pub dek_info: Option<DEKInfo>,
-    /* Not Supported
-    originator: Option<Originator>,
-    mic_info: Option<MICInfo>,
-    recipients: Vec<Recipient>,
-    crl: Option<CRL>,
-    */
+    pub originator: Option<Originator>,
+    pub recipients: Vec<Recipient>,
+    /// CRLs of a `Proc-Type: 4,CRL` message
+    pub crls: Vec<CRLInfo>,
+    /// `Issuer` fields of a `Proc-Type: 4,CRL-RETRIEVAL-REQUEST` message,
+    /// DER encoded names of the issuers whose CRL is requested
+    pub issuers: Vec<Vec<u8>>,
+    /// Every header field, in order of appearance
+    ///
+    /// The typed values win over the fields when formatting: editing a field
+    /// which has a typed counterpart, such as `DEK-Info` or `Key-Info`, has no
+    /// effect on the output unless the typed value is edited as well.
+    pub fields: Vec<HeaderField>,
 }
 
 impl PemHeader {
@@ -27,49 +51,372 @@ impl PemHeader {
 
 impl PemHeader {
     /// Return `true` if the header won't print anything out
-    ///
-    /// # Note
-    /// If the `Proc-Type` header field is not exist,
-    /// The header won't display anything
     pub fn is_empty(&self) -> bool {
         self.proc_type.is_none()
+            && self.content_domain.is_none()
+            && self.dek_info.is_none()
+            && self.originator.is_none()
+            && self.recipients.is_empty()
+            && self.crls.is_empty()
+            && self.issuers.is_empty()
+            && self.fields.is_empty()
+    }
+
+    /// Return the value of the first field named `name`, ignoring case
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.fields
+            .iter()
+            .find(|field| field.name.eq_ignore_ascii_case(name))
+            .map(|field| field.value.as_str())
     }
 
     pub(self) fn from_pair(pair: Pair<Rule>) -> Result<Self, Error<Rule>> {
         let mut hdr = PemHeader::default();
+        let mut rfc1421_entries = Vec::new();
 
         for hdr_entry in pair.into_inner() {
+            if hdr_entry.as_rule() == Rule::EOI {
+                continue;
+            }
+            hdr.fields.push(HeaderField::from_pair(&hdr_entry));
             match hdr_entry.as_rule() {
                 Rule::proctype => hdr.proc_type = Some(ProcType::from_pair(hdr_entry)?),
                 Rule::contentdomain => {
                     hdr.content_domain = Some(ContentDomain::from_pair(hdr_entry)?)
                 }
                 Rule::dekinfo => hdr.dek_info = Some(DEKInfo::from_pair(hdr_entry)?),
-                Rule::unsupported_hdr | Rule::EOI => (),
-                _ => unreachable!(),
+                Rule::field => (),
+                _ => rfc1421_entries.push(hdr_entry),
             }
         }
+        hdr.group_rfc1421(rfc1421_entries)?;
         Ok(hdr)
     }
-}
 
-impl Display for PemHeader {
-    fn fmt(&self, f: &mut Formatter) -> FmtResult {
-        if let Some(proc_type) = &self.proc_type {
-            writeln!(f, "{}", proc_type)?;
-            if let Some(domain) = &self.content_domain {
-                writeln!(f, "{}", domain)?;
+    /// Group the RFC 1421 fields into the originator and the recipients,
+    /// and the RFC 1424 fields into CRLs and issuers
+    ///
+    /// `Key-Info`, `Issuer-Certificate` and `MIC-Info` fields belong to the
+    /// originator or recipient ID field preceding them. In CRL messages,
+    /// `Originator-Certificate` and `Issuer-Certificate` fields belong to the
+    /// `CRL` field preceding them.
+    fn group_rfc1421(&mut self, entries: Vec<Pair<Rule>>) -> Result<(), Error<Rule>> {
+        let mut entries = entries.into_iter().peekable();
+        while let Some(entry) = entries.next() {
+            let rule = entry.as_rule();
+            match rule {
+                Rule::originatoridasym | Rule::originatorcert | Rule::originatoridsym
+                    if self.originator.is_some() =>
+                {
+                    return Err(pest_err_span("Duplicate originator", &entry));
+                }
+                Rule::originatoridasym | Rule::originatorcert => {
+                    let originator_id = if rule == Rule::originatorcert {
+                        AsymmetricOriginator::Cert(Certificate::from_pair(&entry)?)
+                    } else {
+                        AsymmetricOriginator::ID(AsymmetricID::from_pair(&entry)?)
+                    };
+                    let key_info = match entries.next_if(|e| e.as_rule() == Rule::keyinfoasym) {
+                        Some(key_info) => Some(KeyInfoAsymmetric::from_pair(&key_info)?),
+                        None => None,
+                    };
+                    let mut issuer_certificate = Vec::new();
+                    while let Some(cert) = entries.next_if(|e| e.as_rule() == Rule::issuercert) {
+                        issuer_certificate.push(Certificate::from_pair(&cert)?);
+                    }
+                    let mic_info = entries
+                        .next_if(|e| e.as_rule() == Rule::micinfo)
+                        .ok_or_else(|| pest_err_span("Missing MIC-Info field", &entry))?;
+                    self.originator = Some(Originator::Asymmetric {
+                        originator_id,
+                        key_info,
+                        issuer_certificate,
+                        mic_info: MICInfo::from_pair(&mic_info)?,
+                    });
+                }
+                Rule::originatoridsym => {
+                    let key_info = match entries.next_if(|e| e.as_rule() == Rule::keyinfosym) {
+                        Some(key_info) => Some(KeyInfoSymmetric::from_pair(&key_info)?),
+                        None => None,
+                    };
+                    self.originator = Some(Originator::Symmetric {
+                        originator_id: SymmetricID::from_pair(&entry)?,
+                        key_info,
+                    });
+                }
+                Rule::recipientidasym => {
+                    let key_info = entries
+                        .next_if(|e| e.as_rule() == Rule::keyinfoasym)
+                        .ok_or_else(|| pest_err_span("Missing Key-Info field", &entry))?;
+                    self.recipients.push(Recipient::Asymmetric {
+                        recipient_id: AsymmetricID::from_pair(&entry)?,
+                        key_info: KeyInfoAsymmetric::from_pair(&key_info)?,
+                    });
+                }
+                Rule::recipientidsym => {
+                    let key_info = entries
+                        .next_if(|e| e.as_rule() == Rule::keyinfosym)
+                        .ok_or_else(|| pest_err_span("Missing Key-Info field", &entry))?;
+                    self.recipients.push(Recipient::Symmetric {
+                        recipient_id: SymmetricID::from_pair(&entry)?,
+                        key_info: KeyInfoSymmetric::from_pair(&key_info)?,
+                    });
+                }
+                Rule::crl => {
+                    let originator_certificate =
+                        match entries.next_if(|e| e.as_rule() == Rule::originatorcert) {
+                            Some(cert) => Some(Certificate::from_pair(&cert)?),
+                            None => None,
+                        };
+                    let mut issuer_certificate = Vec::new();
+                    while let Some(cert) = entries.next_if(|e| e.as_rule() == Rule::issuercert) {
+                        issuer_certificate.push(Certificate::from_pair(&cert)?);
+                    }
+                    self.crls.push(CRLInfo {
+                        crl: CRL::from_pair(&entry)?,
+                        originator_certificate,
+                        issuer_certificate,
+                    });
+                }
+                Rule::issuer => {
+                    let b64data = entry.clone().into_inner().next().unwrap();
+                    self.issuers.push(decode_base64_pair(&b64data)?);
+                }
+                _ => {
+                    return Err(pest_err_span(
+                        "Field does not follow an originator, recipient ID or CRL",
+                        &entry,
+                    ))
+                }
             }
-            if let Some(dek_info) = &self.dek_info {
-                writeln!(f, "{}", dek_info)?;
+        }
+        Ok(())
+    }
+
+    /// Check whether the typed RFC 1421 values match the ones in `fields`
+    ///
+    /// The typed values are written unfolded and compared with the fields
+    /// as text, ignoring whitespace, rather than parsing the fields again.
+    fn rfc1421_fields_match(&self) -> bool {
+        struct Unfolded<'a>(&'a PemHeader);
+
+        impl Display for Unfolded<'_> {
+            fn fmt(&self, f: &mut Formatter) -> FmtResult {
+                self.0.fmt_rfc1421(f, 0)
             }
         }
+
+        let raw: String = self
+            .fields
+            .iter()
+            .filter(|field| is_rfc1421_name(&field.name))
+            .map(|field| format!("{}\n", field))
+            .collect();
+        squeeze_fields(&raw) == squeeze_fields(&Unfolded(self).to_string())
+    }
+
+    /// Write the typed RFC 1421 values
+    fn fmt_rfc1421(&self, f: &mut Formatter, width: usize) -> FmtResult {
+        if let Some(originator) = &self.originator {
+            originator.fmt_folded(f, width)?;
+            writeln!(f)?;
+        }
+        for recipient in &self.recipients {
+            recipient.fmt_folded(f, width)?;
+            writeln!(f)?;
+        }
+        for crl in &self.crls {
+            crl.fmt_folded(f, width)?;
+            writeln!(f)?;
+        }
+        for issuer in &self.issuers {
+            write!(f, "Issuer:")?;
+            fmt_base64_lines(f, issuer, width)?;
+            writeln!(f)?;
+        }
         Ok(())
     }
+
+    /// Write a typed field unless it is written in place of one of `fields`
+    fn fmt_typed<T: Display>(&self, f: &mut Formatter, name: &str, typed: &Option<T>) -> FmtResult {
+        match typed {
+            Some(value) if !self.fields.iter().any(|field| field.name == name) => {
+                writeln!(f, "{}", value)
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Write one of `fields`, or its typed counterpart if they differ
+    fn fmt_field<T: Display>(
+        f: &mut Formatter,
+        field: &HeaderField,
+        typed: &Option<T>,
+    ) -> FmtResult {
+        match typed {
+            Some(value) => {
+                let value = value.to_string();
+                if value == field.to_string() {
+                    writeln!(f, "{}", field)
+                } else {
+                    writeln!(f, "{}", value)
+                }
+            }
+            None => Ok(()),
+        }
+    }
+
+    /// Write the header, folding the typed base64 values every `width`
+    /// characters
+    ///
+    /// Fields kept as found in the input are written unchanged.
+    fn fmt_folded(&self, f: &mut Formatter, width: usize) -> FmtResult {
+        self.fmt_typed(f, PROC_TYPE, &self.proc_type)?;
+        self.fmt_typed(f, CONTENT_DOMAIN, &self.content_domain)?;
+        self.fmt_typed(f, DEK_INFO, &self.dek_info)?;
+        let rfc1421_raw = self.rfc1421_fields_match();
+        let mut rfc1421_written = rfc1421_raw;
+        for field in &self.fields {
+            match field.name.as_str() {
+                PROC_TYPE => Self::fmt_field(f, field, &self.proc_type)?,
+                CONTENT_DOMAIN => Self::fmt_field(f, field, &self.content_domain)?,
+                DEK_INFO => Self::fmt_field(f, field, &self.dek_info)?,
+                name if is_rfc1421_name(name) => {
+                    if rfc1421_raw {
+                        writeln!(f, "{}", field)?;
+                    } else if !rfc1421_written {
+                        self.fmt_rfc1421(f, width)?;
+                        rfc1421_written = true;
+                    }
+                }
+                _ => writeln!(f, "{}", field)?,
+            }
+        }
+        if !rfc1421_written {
+            self.fmt_rfc1421(f, width)?;
+        }
+        Ok(())
+    }
+
+    /// The header as text, see [`EncodeOptions`](crate::EncodeOptions)
+    pub(crate) fn to_string_folded(&self, width: usize) -> String {
+        struct Folded<'a>(&'a PemHeader, usize);
+
+        impl Display for Folded<'_> {
+            fn fmt(&self, f: &mut Formatter) -> FmtResult {
+                self.0.fmt_folded(f, self.1)
+            }
+        }
+
+        Folded(self, width).to_string()
+    }
+}
+
+impl Display for PemHeader {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        self.fmt_folded(f, FOLD_WIDTH)
+    }
+}
+
+pub(crate) const PROC_TYPE: &str = "Proc-Type";
+pub(crate) const CONTENT_DOMAIN: &str = "Content-Domain";
+pub(crate) const DEK_INFO: &str = "DEK-Info";
+const RFC1421_NAMES: &[&str] = &[
+    "Originator-ID-Asymmetric",
+    "Originator-Certificate",
+    "Originator-ID-Symmetric",
+    "Issuer-Certificate",
+    "MIC-Info",
+    "Key-Info",
+    "Recipient-ID-Asymmetric",
+    "Recipient-ID-Symmetric",
+    "CRL",
+    "Issuer",
+];
+
+fn is_rfc1421_name(name: &str) -> bool {
+    RFC1421_NAMES.contains(&name)
+}
+
+/// Header fields without their whitespace, one per line
+fn squeeze_fields(text: &str) -> String {
+    let mut squeezed = String::with_capacity(text.len());
+    for line in text.lines() {
+        if !line.starts_with([' ', '\t']) && !squeezed.is_empty() {
+            squeezed.push('\n');
+        }
+        squeezed.extend(line.chars().filter(|c| !c.is_ascii_whitespace()));
+    }
+    squeezed
+}
+
+/// A single header field, as found in the input
+#[derive(Debug, Clone, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct HeaderField {
+    pub name: String,
+    /// Field body, without the whitespace following the colon
+    ///
+    /// Continuation lines are kept as is, with their leading whitespace,
+    /// each preceded by a `\n`.
+    pub value: String,
+    /// Location of the field in the parsed input, ignored when comparing
+    /// fields
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub span: Option<SourceSpan>,
+}
+
+impl PartialEq for HeaderField {
+    fn eq(&self, other: &Self) -> bool {
+        self.name == other.name && self.value == other.value
+    }
+}
+
+impl HeaderField {
+    pub fn new<N: Into<String>, V: Into<String>>(name: N, value: V) -> Self {
+        HeaderField {
+            name: name.into(),
+            value: value.into(),
+            span: None,
+        }
+    }
+
+    pub(self) fn from_pair(pair: &Pair<Rule>) -> Self {
+        let raw = pair.as_str().trim_end_matches(['\r', '\n']);
+        let colon = raw.find(':').unwrap();
+        let value = raw[colon + 1..]
+            .trim_start_matches([' ', '\t'])
+            .replace("\r\n", "\n")
+            .replace('\r', "\n");
+        let span = pair.as_span();
+        HeaderField {
+            span: Some(SourceSpan::new(
+                span.get_input(),
+                span.start(),
+                span.start() + raw.len(),
+            )),
+            ..HeaderField::new(raw[..colon].trim_end(), value)
+        }
+    }
+
+    pub(crate) fn rebase_span(&mut self, base: Position) {
+        self.span = self.span.map(|span| span.rebase(base));
+    }
+}
+
+impl Display for HeaderField {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        if self.value.is_empty() || self.value.starts_with('\n') {
+            write!(f, "{}:{}", &self.name, &self.value)
+        } else {
+            write!(f, "{}: {}", &self.name, &self.value)
+        }
+    }
 }
 
 /// `Proc-Type` header field
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ProcType(pub u32, pub ProcTypeSpecifier);
 
 impl ProcType {
@@ -100,11 +447,16 @@ impl Display for ProcType {
 /// Enumerations for different PEM type
 #[allow(non_camel_case_types)]
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum ProcTypeSpecifier {
     ENCRYPTED,
+    #[cfg_attr(feature = "serde", serde(rename = "MIC-ONLY"))]
     MIC_ONLY,
+    #[cfg_attr(feature = "serde", serde(rename = "MIC-CLEAR"))]
     MIC_CLEAR,
     CRL,
+    #[cfg_attr(feature = "serde", serde(rename = "CRL-RETRIEVAL-REQUEST"))]
+    CRL_RETRIEVAL_REQUEST,
 }
 
 impl FromStr for ProcTypeSpecifier {
@@ -116,6 +468,7 @@ impl FromStr for ProcTypeSpecifier {
             "MIC-ONLY" => Ok(MIC_ONLY),
             "MIC-CLEAR" => Ok(MIC_CLEAR),
             "CRL" => Ok(CRL),
+            "CRL-RETRIEVAL-REQUEST" => Ok(CRL_RETRIEVAL_REQUEST),
             _ => Err(()),
         }
     }
@@ -128,12 +481,14 @@ impl Display for ProcTypeSpecifier {
             Self::MIC_ONLY => write!(f, "MIC-ONLY"),
             Self::MIC_CLEAR => write!(f, "MIC-CLEAR"),
             Self::CRL => write!(f, "CRL"),
+            Self::CRL_RETRIEVAL_REQUEST => write!(f, "CRL-RETRIEVAL-REQUEST"),
         }
     }
 }
 
 /// `Content-Domain` header field
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ContentDomain(pub String);
 
 impl ContentDomain {
@@ -152,6 +507,7 @@ impl Display for ContentDomain {
 
 /// `DEK-Info` header field
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct DEKInfo {
     pub algorithm: String,
     pub parameter: Vec<u8>,
@@ -196,114 +552,411 @@ struct HeaderParser;
 impl HeaderParser {
     pub fn parse_str(input: &str) -> Result<PemHeader, Error<Rule>> {
         let pemhdr = HeaderParser::parse(Rule::pemhdr, input)?.next().unwrap();
-        Ok(PemHeader::from_pair(pemhdr)?)
+        PemHeader::from_pair(pemhdr)
     }
 }
 
-/// Some unimplemented things
-#[allow(dead_code)]
+/// The RFC 1421 header types, under their former path
 #[cfg(feature = "unstable")]
-mod unstable {
-    /// Certificate stored in base64 form
-    #[derive(Debug, Clone, PartialEq, Eq)]
-    pub struct Certificate(Vec<u8>);
-
-    /// Certificate Revoked List stored in base64 form
-    #[derive(Debug, Clone, PartialEq, Eq)]
-    pub struct CRL(Vec<u8>);
-
-    /// Represent single recipient related fields
-    #[derive(Debug, Clone, PartialEq, Eq)]
-    pub enum Recipient {
-        Asymmetric {
-            /// `Recipient-ID-Asymmetric` field
-            originator_id: AsymmetricID,
-            /// The following `Key-Info` field
-            key_info: KeyInfoAsymmetric,
-        },
-        Symmetric {
-            /// `Recipient-ID-Symmetric` field
-            originator_id: SymmetricID,
-            /// The following `Key-Info` field
-            key_info: KeyInfoSymmetric,
-        },
-    }
-
-    /// Represent originator related fields
-    #[derive(Debug, Clone, PartialEq, Eq)]
-    pub enum Originator {
-        Asymmetric {
-            /// Asymmetric originator case
-            originator_id: AsymmetricOriginator,
-            /// The following `Key-Info` field (if present)
-            key_info: Option<KeyInfoAsymmetric>,
-            /// Zero or more `Issuer-Certificate` fields
-            issuer_certificate: Vec<Certificate>,
-            /// `MIC-Info` field
-            mic_info: MICInfo,
-        },
-        Symmetric {
-            /// `Originator-ID-Symmetric` field
-            originator_id: SymmetricID,
-            /// The following `Key-Info` field (if present)
-            key_info: Option<KeyInfoSymmetric>,
-        },
-    }
-
-    /// Represent originator using asymmetric in either ID or certificate form
-    #[derive(Debug, Clone, PartialEq, Eq)]
-    pub enum AsymmetricOriginator {
-        /// `Originator-ID-Asymmetric` field
-        ID(AsymmetricID),
-        /// `Originator-Certificate` field
-        Cert(Certificate),
-    }
-
-    /// `Key-Info` field for asymmetric case
-    #[derive(Debug, Clone, PartialEq, Eq)]
-    pub struct KeyInfoAsymmetric {
-        /// Asymmetric algorithm
-        pub algorithm: String,
-        /// Base64 DEK data
-        pub dek: Vec<u8>,
-    }
-
-    /// `Key-Info` field for symmetric case
-    #[derive(Debug, Clone, PartialEq, Eq)]
-    pub struct KeyInfoSymmetric {
-        /// Symmetric algorithm
-        pub algorithm: String,
-        /// Integrity check algorithm
-        pub mic_algorithm: String,
-        /// Hexadecimal DEK data
-        pub dek: Vec<u8>,
-        /// Hexadecimal MIC data
-        pub mic: Vec<u8>,
-    }
-
-    /// Personal ID for asymmetric case
-    ///
-    /// `Originator-ID-Asymmetric` field for originator
-    ///
-    /// `Recipient-ID-Asymmetric` field for recipient
-    #[derive(Debug, Clone, PartialEq, Eq)]
-    pub struct AsymmetricID(pub String, pub String);
+#[deprecated(note = "the types are now in `easypem::headers`")]
+pub mod unstable {
+    pub use super::{
+        AsymmetricID, AsymmetricOriginator, Certificate, KeyInfoAsymmetric, KeyInfoSymmetric,
+        MICInfo, Originator, Recipient, SymmetricID, CRL,
+    };
+}
 
-    /// Personal ID for symmetric case
-    ///
-    /// `Originator-ID-Symmetric` field for originator
-    ///
-    /// `Recipient-ID-Symmetric` field for recipient
-    #[derive(Debug, Clone, PartialEq, Eq)]
-    pub struct SymmetricID(pub String, pub String, pub String);
-
-    #[derive(Debug, Clone, PartialEq, Eq)]
-    pub struct MICInfo {
-        /// Message integrity check algorithm
-        pub algorithm: String,
-        /// IK algorithm
-        pub ik_algorithm: String,
-        /// Base64 signature data
-        pub signature: Vec<u8>,
+/// Default number of base64 characters on a continuation line
+const FOLD_WIDTH: usize = 64;
+
+/// Write `data` in base64, on continuation lines of `width` characters,
+/// or on a single one if `width` is 0
+fn fmt_base64_lines(f: &mut Formatter, data: &[u8], width: usize) -> FmtResult {
+    let text = base64::encode(data);
+    if width == 0 {
+        return write!(f, "\n {}", text);
+    }
+    text.as_bytes()
+        .chunks(width)
+        .map(|v| std::str::from_utf8(v).unwrap())
+        .try_for_each(|s| write!(f, "\n {}", s))
+}
+
+/// Decode a base64 value, ignoring the line folding
+fn decode_base64_pair(pair: &Pair<Rule>) -> Result<Vec<u8>, Error<Rule>> {
+    let text: String = pair
+        .as_str()
+        .chars()
+        .filter(|c| !c.is_ascii_whitespace())
+        .collect();
+    rfc1421_base64_decode(&text).map_err(|e| pest_err_span(e.to_string(), pair))
+}
+
+fn decode_hex_pair(pair: &Pair<Rule>) -> Result<Vec<u8>, Error<Rule>> {
+    hex::decode(pair.as_str()).map_err(|e| pest_err_span(e.to_string(), pair))
+}
+
+/// Certificate stored in base64 form
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Certificate(pub Vec<u8>);
+
+impl Certificate {
+    pub(self) fn from_pair(pair: &Pair<Rule>) -> Result<Self, Error<Rule>> {
+        let b64data = pair.clone().into_inner().next().unwrap();
+        Ok(Certificate(decode_base64_pair(&b64data)?))
+    }
+}
+
+/// Represent single recipient related fields
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Recipient {
+    Asymmetric {
+        /// `Recipient-ID-Asymmetric` field
+        recipient_id: AsymmetricID,
+        /// The following `Key-Info` field
+        key_info: KeyInfoAsymmetric,
+    },
+    Symmetric {
+        /// `Recipient-ID-Symmetric` field
+        recipient_id: SymmetricID,
+        /// The following `Key-Info` field
+        key_info: KeyInfoSymmetric,
+    },
+}
+
+impl Recipient {
+    fn fmt_folded(&self, f: &mut Formatter, width: usize) -> FmtResult {
+        match self {
+            Recipient::Asymmetric {
+                recipient_id,
+                key_info,
+            } => {
+                write!(f, "Recipient-ID-Asymmetric:")?;
+                recipient_id.fmt_value(f, width)?;
+                writeln!(f)?;
+                key_info.fmt_folded(f, width)
+            }
+            Recipient::Symmetric {
+                recipient_id,
+                key_info,
+            } => write!(f, "Recipient-ID-Symmetric: {}\n{}", recipient_id, key_info),
+        }
+    }
+}
+
+impl Display for Recipient {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        self.fmt_folded(f, FOLD_WIDTH)
+    }
+}
+
+/// Represent originator related fields
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Originator {
+    Asymmetric {
+        /// Asymmetric originator case
+        originator_id: AsymmetricOriginator,
+        /// The following `Key-Info` field (if present)
+        key_info: Option<KeyInfoAsymmetric>,
+        /// Zero or more `Issuer-Certificate` fields
+        issuer_certificate: Vec<Certificate>,
+        /// `MIC-Info` field
+        mic_info: MICInfo,
+    },
+    Symmetric {
+        /// `Originator-ID-Symmetric` field
+        originator_id: SymmetricID,
+        /// The following `Key-Info` field (if present)
+        key_info: Option<KeyInfoSymmetric>,
+    },
+}
+
+impl Originator {
+    fn fmt_folded(&self, f: &mut Formatter, width: usize) -> FmtResult {
+        match self {
+            Originator::Asymmetric {
+                originator_id,
+                key_info,
+                issuer_certificate,
+                mic_info,
+            } => {
+                originator_id.fmt_folded(f, width)?;
+                if let Some(key_info) = key_info {
+                    writeln!(f)?;
+                    key_info.fmt_folded(f, width)?;
+                }
+                for cert in issuer_certificate {
+                    write!(f, "\nIssuer-Certificate:")?;
+                    fmt_base64_lines(f, &cert.0, width)?;
+                }
+                writeln!(f)?;
+                mic_info.fmt_folded(f, width)
+            }
+            Originator::Symmetric {
+                originator_id,
+                key_info,
+            } => {
+                write!(f, "Originator-ID-Symmetric: {}", originator_id)?;
+                if let Some(key_info) = key_info {
+                    write!(f, "\n{}", key_info)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl Display for Originator {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        self.fmt_folded(f, FOLD_WIDTH)
+    }
+}
+
+/// Represent originator using asymmetric in either ID or certificate form
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum AsymmetricOriginator {
+    /// `Originator-ID-Asymmetric` field
+    ID(AsymmetricID),
+    /// `Originator-Certificate` field
+    Cert(Certificate),
+}
+
+impl AsymmetricOriginator {
+    fn fmt_folded(&self, f: &mut Formatter, width: usize) -> FmtResult {
+        match self {
+            AsymmetricOriginator::ID(id) => {
+                write!(f, "Originator-ID-Asymmetric:")?;
+                id.fmt_value(f, width)
+            }
+            AsymmetricOriginator::Cert(cert) => {
+                write!(f, "Originator-Certificate:")?;
+                fmt_base64_lines(f, &cert.0, width)
+            }
+        }
+    }
+}
+
+impl Display for AsymmetricOriginator {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        self.fmt_folded(f, FOLD_WIDTH)
+    }
+}
+
+/// `Key-Info` field for asymmetric case
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct KeyInfoAsymmetric {
+    /// Asymmetric algorithm
+    pub algorithm: String,
+    /// Base64 DEK data
+    pub dek: Vec<u8>,
+}
+
+impl KeyInfoAsymmetric {
+    pub(self) fn from_pair(pair: &Pair<Rule>) -> Result<Self, Error<Rule>> {
+        let mut pairs = pair.clone().into_inner();
+        let algorithm = pairs.next().unwrap().as_str().to_owned();
+        let dek = decode_base64_pair(&pairs.next().unwrap())?;
+        Ok(KeyInfoAsymmetric { algorithm, dek })
+    }
+
+    fn fmt_folded(&self, f: &mut Formatter, width: usize) -> FmtResult {
+        write!(f, "Key-Info: {},", &self.algorithm)?;
+        fmt_base64_lines(f, &self.dek, width)
+    }
+}
+
+impl Display for KeyInfoAsymmetric {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        self.fmt_folded(f, FOLD_WIDTH)
+    }
+}
+
+/// `Key-Info` field for symmetric case
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct KeyInfoSymmetric {
+    /// Symmetric algorithm
+    pub algorithm: String,
+    /// Integrity check algorithm
+    pub mic_algorithm: String,
+    /// Hexadecimal DEK data
+    pub dek: Vec<u8>,
+    /// Hexadecimal MIC data
+    pub mic: Vec<u8>,
+}
+
+impl KeyInfoSymmetric {
+    pub(self) fn from_pair(pair: &Pair<Rule>) -> Result<Self, Error<Rule>> {
+        let mut pairs = pair.clone().into_inner();
+        let algorithm = pairs.next().unwrap().as_str().to_owned();
+        let mic_algorithm = pairs.next().unwrap().as_str().to_owned();
+        let dek = decode_hex_pair(&pairs.next().unwrap())?;
+        let mic = decode_hex_pair(&pairs.next().unwrap())?;
+        Ok(KeyInfoSymmetric {
+            algorithm,
+            mic_algorithm,
+            dek,
+            mic,
+        })
+    }
+}
+
+impl Display for KeyInfoSymmetric {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        write!(
+            f,
+            "Key-Info: {},{},{},{}",
+            &self.algorithm,
+            &self.mic_algorithm,
+            hex::encode_upper(&self.dek),
+            hex::encode_upper(&self.mic)
+        )
+    }
+}
+
+/// Personal ID for asymmetric case
+///
+/// `Originator-ID-Asymmetric` field for originator
+///
+/// `Recipient-ID-Asymmetric` field for recipient
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AsymmetricID {
+    /// DER encoded issuer name
+    pub issuer: Vec<u8>,
+    /// Serial number of the certificate, in hexadecimal
+    pub serial_number: String,
+}
+
+impl AsymmetricID {
+    pub(self) fn from_pair(pair: &Pair<Rule>) -> Result<Self, Error<Rule>> {
+        let mut pairs = pair.clone().into_inner();
+        let issuer = decode_base64_pair(&pairs.next().unwrap())?;
+        let serial_number = pairs.next().unwrap().as_str().to_owned();
+        Ok(AsymmetricID {
+            issuer,
+            serial_number,
+        })
+    }
+
+    /// Write the field value, folded as in RFC 1421
+    fn fmt_value(&self, f: &mut Formatter, width: usize) -> FmtResult {
+        fmt_base64_lines(f, &self.issuer, width)?;
+        write!(f, ",\n {}", &self.serial_number)
+    }
+}
+
+/// Personal ID for symmetric case
+///
+/// `Originator-ID-Symmetric` field for originator
+///
+/// `Recipient-ID-Symmetric` field for recipient
+///
+/// Holds the entity identifier, the issuing authority and the version,
+/// the last two may be empty.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SymmetricID(pub String, pub String, pub String);
+
+impl SymmetricID {
+    pub(self) fn from_pair(pair: &Pair<Rule>) -> Result<Self, Error<Rule>> {
+        let mut pairs = pair.clone().into_inner();
+        let mut next = || pairs.next().unwrap().as_str().to_owned();
+        Ok(SymmetricID(next(), next(), next()))
+    }
+}
+
+impl Display for SymmetricID {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        write!(f, "{},{},{}", &self.0, &self.1, &self.2)
+    }
+}
+
+/// `MIC-Info` field
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MICInfo {
+    /// Message integrity check algorithm
+    pub algorithm: String,
+    /// IK algorithm
+    pub ik_algorithm: String,
+    /// Base64 signature data
+    pub signature: Vec<u8>,
+}
+
+impl MICInfo {
+    pub(self) fn from_pair(pair: &Pair<Rule>) -> Result<Self, Error<Rule>> {
+        let mut pairs = pair.clone().into_inner();
+        let algorithm = pairs.next().unwrap().as_str().to_owned();
+        let ik_algorithm = pairs.next().unwrap().as_str().to_owned();
+        let signature = decode_base64_pair(&pairs.next().unwrap())?;
+        Ok(MICInfo {
+            algorithm,
+            ik_algorithm,
+            signature,
+        })
+    }
+
+    fn fmt_folded(&self, f: &mut Formatter, width: usize) -> FmtResult {
+        write!(f, "MIC-Info: {},{},", &self.algorithm, &self.ik_algorithm)?;
+        fmt_base64_lines(f, &self.signature, width)
+    }
+}
+
+impl Display for MICInfo {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        self.fmt_folded(f, FOLD_WIDTH)
+    }
+}
+
+/// Certificate Revoked List stored in base64 form
+#[allow(clippy::upper_case_acronyms)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CRL(pub Vec<u8>);
+
+impl CRL {
+    pub(self) fn from_pair(pair: &Pair<Rule>) -> Result<Self, Error<Rule>> {
+        let b64data = pair.clone().into_inner().next().unwrap();
+        Ok(CRL(decode_base64_pair(&b64data)?))
+    }
+}
+
+/// Represent a CRL of an RFC 1424 CRL message, with its certificates
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CRLInfo {
+    /// `CRL` field
+    pub crl: CRL,
+    /// The following `Originator-Certificate` field (if present)
+    pub originator_certificate: Option<Certificate>,
+    /// Zero or more `Issuer-Certificate` fields
+    pub issuer_certificate: Vec<Certificate>,
+}
+
+impl CRLInfo {
+    fn fmt_folded(&self, f: &mut Formatter, width: usize) -> FmtResult {
+        write!(f, "CRL:")?;
+        fmt_base64_lines(f, &self.crl.0, width)?;
+        if let Some(cert) = &self.originator_certificate {
+            write!(f, "\nOriginator-Certificate:")?;
+            fmt_base64_lines(f, &cert.0, width)?;
+        }
+        for cert in &self.issuer_certificate {
+            write!(f, "\nIssuer-Certificate:")?;
+            fmt_base64_lines(f, &cert.0, width)?;
+        }
+        Ok(())
+    }
+}
+
+impl Display for CRLInfo {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        self.fmt_folded(f, FOLD_WIDTH)
     }
 }
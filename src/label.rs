@@ -0,0 +1,235 @@
+use crate::der::{self, DerReader};
+use crate::*;
+use std::convert::Infallible;
+
+/// Known PEM labels
+///
+/// Covers the labels of RFC 7468 and the legacy labels commonly found in
+/// the wild. Any other label is kept as `Other`, which can only be built by
+/// parsing, so that two labels are equal whenever their text is.
+///
+/// ```
+/// # use easypem::PemLabel;
+/// let label: PemLabel = "X509 CERTIFICATE".parse().unwrap();
+/// assert_eq!(label, PemLabel::X509Certificate);
+/// assert!(!label.is_rfc7468());
+/// assert_eq!(label.preferred(), PemLabel::Certificate);
+/// assert_eq!(label.preferred().to_string(), "CERTIFICATE");
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum PemLabel {
+    Certificate,
+    X509Crl,
+    CertificateRequest,
+    Pkcs7,
+    Cms,
+    PrivateKey,
+    EncryptedPrivateKey,
+    AttributeCertificate,
+    PublicKey,
+    RsaPrivateKey,
+    EcPrivateKey,
+    DsaPrivateKey,
+    X509Certificate,
+    /// `X.509 CERTIFICATE`, an alias of `CERTIFICATE` listed by RFC 7468
+    X509DotCertificate,
+    TrustedCertificate,
+    NewCertificateRequest,
+    /// `CRL`, an alias of `X509 CRL` found in the wild
+    Crl,
+    OpensshPrivateKey,
+    DhParameters,
+    EcParameters,
+    Other(OtherLabel),
+}
+
+/// Text of a label which is not one of [`PemLabel::KNOWN`]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct OtherLabel(String);
+
+impl OtherLabel {
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl Display for OtherLabel {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        f.write_str(&self.0)
+    }
+}
+
+impl PemLabel {
+    /// Every known label, `Other` excluded
+    pub const KNOWN: &'static [PemLabel] = &[
+        PemLabel::Certificate,
+        PemLabel::X509Crl,
+        PemLabel::CertificateRequest,
+        PemLabel::Pkcs7,
+        PemLabel::Cms,
+        PemLabel::PrivateKey,
+        PemLabel::EncryptedPrivateKey,
+        PemLabel::AttributeCertificate,
+        PemLabel::PublicKey,
+        PemLabel::RsaPrivateKey,
+        PemLabel::EcPrivateKey,
+        PemLabel::DsaPrivateKey,
+        PemLabel::X509Certificate,
+        PemLabel::X509DotCertificate,
+        PemLabel::TrustedCertificate,
+        PemLabel::NewCertificateRequest,
+        PemLabel::Crl,
+        PemLabel::OpensshPrivateKey,
+        PemLabel::DhParameters,
+        PemLabel::EcParameters,
+    ];
+
+    pub fn as_str(&self) -> &str {
+        match self {
+            PemLabel::Certificate => CERTIFICATE_LABEL,
+            PemLabel::X509Crl => CRL_LABEL,
+            PemLabel::CertificateRequest => CERTREQ_LABEL,
+            PemLabel::Pkcs7 => PKCS7_LABEL,
+            PemLabel::Cms => CMS_LABEL,
+            PemLabel::PrivateKey => PRIVKEY_LABEL,
+            PemLabel::EncryptedPrivateKey => ENC_PRIVKEY_LABEL,
+            PemLabel::AttributeCertificate => ATTRCERT_LABEL,
+            PemLabel::PublicKey => PUBKEY_LABEL,
+            PemLabel::RsaPrivateKey => "RSA PRIVATE KEY",
+            PemLabel::EcPrivateKey => "EC PRIVATE KEY",
+            PemLabel::DsaPrivateKey => "DSA PRIVATE KEY",
+            PemLabel::X509Certificate => "X509 CERTIFICATE",
+            PemLabel::X509DotCertificate => "X.509 CERTIFICATE",
+            PemLabel::TrustedCertificate => "TRUSTED CERTIFICATE",
+            PemLabel::NewCertificateRequest => "NEW CERTIFICATE REQUEST",
+            PemLabel::Crl => "CRL",
+            PemLabel::OpensshPrivateKey => "OPENSSH PRIVATE KEY",
+            PemLabel::DhParameters => "DH PARAMETERS",
+            PemLabel::EcParameters => "EC PARAMETERS",
+            PemLabel::Other(label) => label.as_str(),
+        }
+    }
+
+    /// Whether this label is defined by RFC 7468
+    pub fn is_rfc7468(&self) -> bool {
+        matches!(
+            self,
+            PemLabel::Certificate
+                | PemLabel::X509Crl
+                | PemLabel::CertificateRequest
+                | PemLabel::Pkcs7
+                | PemLabel::Cms
+                | PemLabel::PrivateKey
+                | PemLabel::EncryptedPrivateKey
+                | PemLabel::AttributeCertificate
+                | PemLabel::PublicKey
+        )
+    }
+
+    /// The label RFC 7468 prefers for the same content
+    ///
+    /// Legacy aliases listed by RFC 7468, and `CRL`, map to their standard
+    /// label, every other label is returned unchanged.
+    pub fn preferred(&self) -> PemLabel {
+        match self {
+            PemLabel::X509Certificate | PemLabel::X509DotCertificate => PemLabel::Certificate,
+            PemLabel::NewCertificateRequest => PemLabel::CertificateRequest,
+            PemLabel::Crl => PemLabel::X509Crl,
+            label => label.clone(),
+        }
+    }
+
+    /// Guess the label of DER encoded `der` from its outer ASN.1 structure
+    ///
+    /// Recognizes X.509 certificates, CRLs and attribute certificates,
+    /// PKCS #10 certification requests, PKCS #8 private keys, encrypted or
+    /// not, subject public key infos and CMS content infos. Returns `None`
+    /// for anything else.
+    ///
+    /// ```
+    /// # use easypem::PemLabel;
+    /// // SubjectPublicKeyInfo { algorithm, subjectPublicKey }
+    /// let spki = [0x30, 0x08, 0x30, 0x02, 0x06, 0x00, 0x03, 0x02, 0x00, 0x00];
+    /// assert_eq!(PemLabel::infer(&spki), Some(PemLabel::PublicKey));
+    /// assert_eq!(PemLabel::infer(b"not DER"), None);
+    /// ```
+    pub fn infer(der: &[u8]) -> Option<PemLabel> {
+        use der::{
+            BIT_STRING as BITS, CONTEXT_0 as CTX0, GENERALIZED_TIME, INTEGER as INT,
+            OCTET_STRING as OCTETS, OID, SEQUENCE as SEQ, UTC_TIME,
+        };
+
+        let mut outer = DerReader::new(der);
+        let content = outer.read(SEQ)?;
+        if !outer.is_empty() {
+            return None;
+        }
+        let label = match DerReader::new(content).tags()?.as_slice() {
+            // ContentInfo { contentType, [0] content }
+            [OID, CTX0] => PemLabel::Cms,
+            // SubjectPublicKeyInfo { algorithm, subjectPublicKey }
+            [SEQ, BITS] => PemLabel::PublicKey,
+            // EncryptedPrivateKeyInfo { encryptionAlgorithm, encryptedData }
+            [SEQ, OCTETS] => PemLabel::EncryptedPrivateKey,
+            // PrivateKeyInfo { version, privateKeyAlgorithm, privateKey, ... }
+            [INT, SEQ, OCTETS, ..] => PemLabel::PrivateKey,
+            // Signed structure { tbs, signatureAlgorithm, signature }
+            [SEQ, SEQ, BITS] => {
+                let tbs = DerReader::new(DerReader::new(content).read(SEQ)?);
+                match tbs.tags()?.as_slice() {
+                    // TBSCertificate { [0] version, serialNumber, signature,
+                    // issuer, validity, subject, subjectPublicKeyInfo, ... }
+                    [CTX0, INT, SEQ, SEQ, SEQ, SEQ, SEQ, ..]
+                    | [INT, SEQ, SEQ, SEQ, SEQ, SEQ, ..] => PemLabel::Certificate,
+                    // TBSCertList { version, signature, issuer, thisUpdate, ... }
+                    [INT, SEQ, SEQ, time, ..] | [SEQ, SEQ, time, ..]
+                        if *time == UTC_TIME || *time == GENERALIZED_TIME =>
+                    {
+                        PemLabel::X509Crl
+                    }
+                    // CertificationRequestInfo { version, subject,
+                    // subjectPKInfo, [0] attributes }
+                    [INT, SEQ, SEQ, CTX0] => PemLabel::CertificateRequest,
+                    // AttributeCertificateInfo { version, holder, issuer,
+                    // signature, serialNumber, attrCertValidityPeriod, ... }
+                    [INT, SEQ, CTX0, SEQ, INT, SEQ, ..] => PemLabel::AttributeCertificate,
+                    _ => return None,
+                }
+            }
+            _ => return None,
+        };
+        Some(label)
+    }
+}
+
+impl Display for PemLabel {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for PemLabel {
+    type Err = Infallible;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(PemLabel::from(s))
+    }
+}
+
+impl From<&str> for PemLabel {
+    fn from(s: &str) -> Self {
+        PemLabel::KNOWN
+            .iter()
+            .find(|label| label.as_str() == s)
+            .cloned()
+            .unwrap_or_else(|| PemLabel::Other(OtherLabel(s.to_owned())))
+    }
+}
+
+impl From<PemLabel> for String {
+    fn from(label: PemLabel) -> Self {
+        match label {
+            PemLabel::Other(OtherLabel(label)) => label,
+            label => label.as_str().to_owned(),
+        }
+    }
+}
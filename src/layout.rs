@@ -0,0 +1,160 @@
+use crate::headers::PemHeader;
+use crate::parser::{rfc1421_base64_decode, RawBlock};
+use crate::{EncodeOptions, PemMessage};
+use std::fmt::{Formatter, Result as FmtResult};
+
+/// Source text of a block around its decoded parts, recorded when parsing
+/// with [`ParseOptions::lossless`](crate::ParseOptions::lossless)
+///
+/// It holds whatever the parser skips: the whitespace and line ending of
+/// every line, the width of every base64 line and the header section as
+/// found. `Display` writes the unchanged parts of the message back from it,
+/// and the edited ones with [`format`](SourceLayout::format).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SourceLayout {
+    /// Format of the source, for the parts which changed
+    format: EncodeOptions,
+    /// Whitespace between the explanatory text and the pre-encapsulation
+    /// boundary
+    before_pre_eb: String,
+    /// Text from the end of the pre-encapsulation boundary to the headers,
+    /// or to the first base64 character
+    after_pre_eb: String,
+    /// Header section, with the header parsed from it
+    headers: Option<(String, PemHeader)>,
+    /// Text from the end of the header section to the first base64 character
+    before_content: String,
+    /// Length of each run of base64 characters, with the text following it
+    /// up to the next run or to the post-encapsulation boundary
+    runs: Vec<(usize, String)>,
+    /// Base64 text, when it is not the canonical encoding of the content
+    base64: Option<String>,
+    /// Line ending following the post-encapsulation boundary
+    after_post_eb: String,
+}
+
+fn is_base64_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || matches!(c, '+' | '/' | '=')
+}
+
+impl SourceLayout {
+    /// Record the layout of `block`, found in `input` and formatted with
+    /// `format`, from which `headers` and `content` were parsed
+    pub(crate) fn record(
+        block: &RawBlock,
+        input: &str,
+        format: EncodeOptions,
+        headers: &PemHeader,
+        content: &[u8],
+    ) -> Self {
+        let body_end = block.post_eb.start;
+        let (after_pre_eb, headers, content_start) = match &block.headers {
+            Some(range) => (
+                &input[block.pre_eb.end..range.start],
+                Some((input[range.clone()].to_owned(), headers.clone())),
+                range.end,
+            ),
+            None => ("", None, block.pre_eb.end),
+        };
+
+        let body = &input[content_start..body_end];
+        let first = body.find(is_base64_char).unwrap_or(body.len());
+        let mut runs = Vec::new();
+        let mut base64 = String::new();
+        let mut rest = &body[first..];
+        while !rest.is_empty() {
+            let width = rest.find(|c| !is_base64_char(c)).unwrap_or(rest.len());
+            let gap = rest[width..]
+                .find(is_base64_char)
+                .unwrap_or(rest.len() - width);
+            base64.push_str(&rest[..width]);
+            runs.push((width, rest[width..width + gap].to_owned()));
+            rest = &rest[width + gap..];
+        }
+
+        let (after_pre_eb, before_content) = match headers {
+            Some(_) => (after_pre_eb, &body[..first]),
+            None => (&body[..first], ""),
+        };
+        let after_post_eb = &input[block.post_eb.end..];
+        let eol_len = match after_post_eb.as_bytes() {
+            [b'\r', b'\n', ..] => 2,
+            [b'\r' | b'\n', ..] => 1,
+            _ => 0,
+        };
+        SourceLayout {
+            format,
+            before_pre_eb: input[block.explanatory.end..block.pre_eb.start].to_owned(),
+            after_pre_eb: after_pre_eb.to_owned(),
+            headers,
+            before_content: before_content.to_owned(),
+            runs,
+            base64: Some(base64).filter(|text| *text != base64::encode(content)),
+            after_post_eb: after_post_eb[..eol_len].to_owned(),
+        }
+    }
+
+    /// Line width, line ending and header width found in the source,
+    /// with which the parts of the message which changed are written
+    pub fn format(&self) -> &EncodeOptions {
+        &self.format
+    }
+
+    /// Write `pem` with this layout, formatting the parts which changed
+    /// since it was recorded
+    pub(crate) fn fmt_message(&self, pem: &PemMessage, f: &mut Formatter) -> FmtResult {
+        let options = &self.format;
+        let eol = options.line_ending.as_str();
+        write!(
+            f,
+            "{}{}-----BEGIN {}-----",
+            pem.explanatory_text(),
+            &self.before_pre_eb,
+            &pem.label
+        )?;
+
+        match &self.headers {
+            Some((text, headers)) if *headers == pem.headers => {
+                write!(f, "{}{}{}", &self.after_pre_eb, text, &self.before_content)?;
+            }
+            _ if pem.headers.is_empty() => f.write_str(&self.after_pre_eb)?,
+            source => {
+                // A line ending must separate the boundary from the headers
+                if self.after_pre_eb.ends_with(['\r', '\n']) {
+                    f.write_str(&self.after_pre_eb)?;
+                } else {
+                    f.write_str(eol)?;
+                }
+                pem.fmt_headers(f, options)?;
+                match source {
+                    Some(_) => f.write_str(&self.before_content)?,
+                    None => f.write_str(eol)?,
+                }
+            }
+        }
+
+        let encoded;
+        let text = match &self.base64 {
+            Some(text)
+                if rfc1421_base64_decode(text).is_ok_and(|content| content == pem.content) =>
+            {
+                text
+            }
+            _ => {
+                encoded = base64::encode(&pem.content);
+                &encoded
+            }
+        };
+        if self.runs.iter().map(|(width, _)| width).sum::<usize>() == text.len() {
+            let mut rest = text.as_str();
+            for (width, gap) in &self.runs {
+                write!(f, "{}{}", &rest[..*width], gap)?;
+                rest = &rest[*width..];
+            }
+        } else {
+            pem.fmt_content(f, options)?;
+        }
+
+        write!(f, "-----END {}-----{}", &pem.label, &self.after_post_eb)
+    }
+}
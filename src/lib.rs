@@ -8,54 +8,318 @@ use std::fmt::{Display, Error as FmtError, Formatter, Result as FmtResult};
 use std::str::FromStr;
 
 mod builder;
+#[cfg(any(feature = "traditional", feature = "pkcs8"))]
+mod cipher;
+mod der;
 pub mod error;
 pub mod headers;
+mod label;
+mod layout;
+pub mod lint;
 mod parser;
+mod pem_ref;
+#[cfg(feature = "pkcs8")]
+pub mod pkcs8;
+mod reader;
+mod scanner;
+#[cfg(feature = "serde")]
+pub mod serde;
+mod span;
+#[cfg(feature = "traditional")]
+pub mod traditional;
+mod writer;
+
+pub use crate::builder::PemBuilder;
+pub use crate::label::{OtherLabel, PemLabel};
+pub use crate::layout::SourceLayout;
+use crate::parser::PemBytesBlocks;
+pub use crate::parser::{ParseOptions, PemBlocks, Strictness};
+pub use crate::pem_ref::PemRef;
+pub use crate::reader::PemReader;
+pub use crate::span::{BlockSpans, SourceSpan};
+pub use crate::writer::{EncodeOptions, LineEnding, PemWriter};
 
 /// Represent a PEM data
 ///
+/// Build one with [`PemMessage::new`] or [`PemBuilder`], or parse it. Two
+/// messages are equal when their label, headers, content and explanatory
+/// text are, whatever their [`layout`](PemMessage::layout) and
+/// [`spans`](PemMessage::spans).
+///
 /// ```
-/// # use easypem::{PemMessage, headers::PemHeader};
+/// # use easypem::PemMessage;
 ///
-/// let pem = PemMessage {
-///     label: "MESSAGE".to_owned(),
-///     headers: PemHeader::default(),
-///     content: b"This is a message".to_vec(),
-/// };
+/// let pem = PemMessage::new("MESSAGE", &b"This is a message"[..]);
 ///
 /// println!("{}", &pem);
 /// ```
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, Default)]
 pub struct PemMessage {
     pub label: String,
     pub headers: headers::PemHeader,
     pub content: Vec<u8>,
+    source: Source,
+}
+
+/// Text around a message, and where it was found
+#[derive(Clone, Debug, Default)]
+struct Source {
+    explanatory_text: String,
+    layout: Option<SourceLayout>,
+    spans: Option<BlockSpans>,
 }
 
+impl PartialEq for PemMessage {
+    fn eq(&self, other: &Self) -> bool {
+        self.label == other.label
+            && self.headers == other.headers
+            && self.content == other.content
+            && self.source.explanatory_text == other.source.explanatory_text
+    }
+}
+
+impl Eq for PemMessage {}
+
+/// Write the message as PEM text
+///
+/// A message with a [`layout`](PemMessage::layout) is written with it,
+/// other ones like [`encode_with`](PemMessage::encode_with) does with the
+/// default options.
+///
+/// Like any `Display` implementation, this fails with a bare
+/// [`fmt::Error`](std::fmt::Error), which makes `to_string` panic, if the
+/// label is empty. [`encode`](PemMessage::encode) reports it instead.
 impl Display for PemMessage {
     fn fmt(&self, f: &mut Formatter) -> FmtResult {
         if self.label.is_empty() {
             return Err(FmtError);
         }
-        writeln!(f, "-----BEGIN {}-----", &self.label)?;
-        write!(f, "{}", &self.headers)?;
+        match &self.source.layout {
+            Some(layout) => layout.fmt_message(self, f),
+            None => self.fmt_with(f, &EncodeOptions::default()),
+        }
+    }
+}
+
+impl PemMessage {
+    /// Message labelled `label` holding `content`, without any header
+    pub fn new<L: Into<String>, C: Into<Vec<u8>>>(label: L, content: C) -> Self {
+        PemMessage {
+            label: label.into(),
+            content: content.into(),
+            ..Default::default()
+        }
+    }
+
+    /// The label as a [`PemLabel`]
+    ///
+    /// ```
+    /// # use easypem::{PemLabel, PemMessage};
+    /// let pem = PemMessage::new("EC PRIVATE KEY", Vec::new());
+    /// assert_eq!(pem.typed_label(), PemLabel::EcPrivateKey);
+    /// ```
+    pub fn typed_label(&self) -> PemLabel {
+        PemLabel::from(self.label.as_str())
+    }
+
+    /// Explanatory text found before the pre-encapsulation boundary
+    ///
+    /// It is kept verbatim, including the line endings. `Display` only
+    /// writes it back along with a [`layout`](PemMessage::layout).
+    pub fn explanatory_text(&self) -> &str {
+        &self.source.explanatory_text
+    }
+
+    pub fn set_explanatory_text<S: Into<String>>(&mut self, text: S) {
+        self.source.explanatory_text = text.into();
+    }
+
+    /// Source text around the parts of the message, recorded when parsing
+    /// with [`ParseOptions::lossless`]
+    ///
+    /// When set, `Display` writes back the explanatory text and the parts of
+    /// the message which are unchanged exactly as they were found, and the
+    /// other ones with [`SourceLayout::format`].
+    pub fn layout(&self) -> Option<&SourceLayout> {
+        self.source.layout.as_ref()
+    }
+
+    /// Replace the layout, `None` making `Display` use the default format
+    pub fn set_layout(&mut self, layout: Option<SourceLayout>) {
+        self.source.layout = layout;
+    }
+
+    /// Location of the block in the parsed input, set by the parsers
+    pub fn spans(&self) -> Option<BlockSpans> {
+        self.source.spans
+    }
+
+    /// Write the message as PEM text, like `Display` does
+    ///
+    /// # Errors
+    ///
+    /// Fails with [`ErrorKind::InvalidLabel`](error::ErrorKind::InvalidLabel)
+    /// if the label is malformed.
+    pub fn encode(&self) -> error::PemResult<String> {
+        parser::validate_label(&self.label)?;
+        Ok(self.to_string())
+    }
+
+    /// Write the message as PEM text formatted according to `options`
+    ///
+    /// The default options give the same text as `Display` for a message
+    /// without [`layout`](PemMessage::layout). The explanatory text is not
+    /// written.
+    pub fn encode_with(&self, options: &EncodeOptions) -> error::PemResult<String> {
+        struct Encoded<'a>(&'a PemMessage, &'a EncodeOptions);
+
+        impl Display for Encoded<'_> {
+            fn fmt(&self, f: &mut Formatter) -> FmtResult {
+                self.0.fmt_with(f, self.1)
+            }
+        }
+
+        parser::validate_label(&self.label)?;
+        Ok(Encoded(self, options).to_string())
+    }
+
+    fn fmt_with(&self, f: &mut Formatter, options: &EncodeOptions) -> FmtResult {
+        if self.label.is_empty() {
+            return Err(FmtError);
+        }
+        let eol = options.line_ending.as_str();
+        write!(f, "-----BEGIN {}-----{}", &self.label, eol)?;
         if !self.headers.is_empty() {
-            writeln!(f)?;
+            self.fmt_headers(f, options)?;
+            f.write_str(eol)?;
         }
-        base64::encode(&self.content)
+        self.fmt_content(f, options)?;
+        write!(f, "-----END {}-----", &self.label)?;
+        if options.trailing_newline {
+            f.write_str(eol)?;
+        }
+        Ok(())
+    }
+
+    /// Write the header lines, each followed by a line ending
+    pub(crate) fn fmt_headers(&self, f: &mut Formatter, options: &EncodeOptions) -> FmtResult {
+        let headers = self.headers.to_string_folded(options.header_width);
+        f.write_str(&headers.replace('\n', options.line_ending.as_str()))
+    }
+
+    /// Write the base64 lines, each followed by a line ending
+    pub(crate) fn fmt_content(&self, f: &mut Formatter, options: &EncodeOptions) -> FmtResult {
+        let content = base64::encode(&self.content);
+        let width = match options.line_width {
+            0 => content.len().max(1),
+            width => width,
+        };
+        content
             .as_bytes()
-            .chunks(64)
+            .chunks(width)
             .map(|v| std::str::from_utf8(v).unwrap())
-            .map(|s| writeln!(f, "{}", s))
-            .collect::<FmtResult>()?;
-        write!(f, "-----END {}-----", &self.label)
+            .try_for_each(|s| write!(f, "{}{}", s, options.line_ending.as_str()))
+    }
+
+    /// Wrap DER encoded `der` in a message labelled `label`
+    ///
+    /// [`PemLabel::infer`] can guess the label of common structures.
+    ///
+    /// ```
+    /// # use easypem::{PemLabel, PemMessage};
+    /// let spki = [0x30, 0x08, 0x30, 0x02, 0x06, 0x00, 0x03, 0x02, 0x00, 0x00];
+    /// let label = PemLabel::infer(&spki).unwrap();
+    /// let pem = PemMessage::from_der(label.as_str(), spki.to_vec()).unwrap();
+    /// assert_eq!(pem.label, "PUBLIC KEY");
+    /// assert_eq!(pem.to_der().unwrap(), spki);
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Fails with [`ErrorKind::InvalidLabel`](error::ErrorKind::InvalidLabel)
+    /// if `label` is malformed, and with
+    /// [`ErrorKind::InvalidDer`](error::ErrorKind::InvalidDer) if `der` is not
+    /// a single DER element.
+    pub fn from_der<D: Into<Vec<u8>>>(label: &str, der: D) -> error::PemResult<PemMessage> {
+        let pem = PemBuilder::new().label(label).content(der).build()?;
+        pem.to_der()?;
+        Ok(pem)
+    }
+
+    /// The content, checked to be a single DER element
+    ///
+    /// Encrypted messages must be decrypted first.
+    pub fn to_der(&self) -> error::PemResult<&[u8]> {
+        let message = if self.headers.dek_info.is_some() {
+            "The content is encrypted"
+        } else if !der::is_single_element(&self.content) {
+            "The content is not a single DER element"
+        } else {
+            return Ok(&self.content);
+        };
+        Err(error::Error::new(error::ErrorKind::InvalidDer, message))
+    }
+
+    /// Parse every PEM block in `input`, in order
+    ///
+    /// Text outside the encapsulation boundaries is ignored.
+    /// The first block failing to parse aborts the whole operation;
+    /// use [`PemBlocks`] to get an error for each block instead.
+    pub fn parse_all(input: &str) -> error::PemResult<Vec<PemMessage>> {
+        PemBlocks::new(input).collect()
+    }
+
+    /// Parse every PEM block in `input` with the given options
+    pub fn parse_all_with(
+        input: &str,
+        options: &ParseOptions,
+    ) -> error::PemResult<Vec<PemMessage>> {
+        PemBlocks::with_options(input, options.clone()).collect()
+    }
+
+    /// Parse a single PEM block with the given options
+    pub fn parse_with(input: &str, options: &ParseOptions) -> error::PemResult<PemMessage> {
+        parser::pem_parser(input, options)
+    }
+
+    /// Parse the first PEM block in `input`, without requiring UTF-8
+    ///
+    /// Non-ASCII bytes are only allowed outside the blocks; the explanatory
+    /// text is converted lossily. Inside a block, the first non-ASCII byte is
+    /// reported with its offset.
+    ///
+    /// ```
+    /// # use easypem::{PemMessage, error::ErrorKind};
+    /// let bytes = b"Issuer: CN=M\xFCller
+    /// -----BEGIN MESSAGE-----
+    /// Zmlyc3Q=
+    /// -----END MESSAGE-----";
+    /// let pem = PemMessage::from_bytes(bytes).unwrap();
+    /// assert_eq!(pem.content, b"first");
+    /// assert_eq!(pem.explanatory_text(), "Issuer: CN=M\u{FFFD}ller\n");
+    ///
+    /// let err = PemMessage::from_bytes(b"-----BEGIN MESSAGE-----\nZml\xFF\n").unwrap_err();
+    /// assert_eq!(err.kind(), ErrorKind::NonAscii);
+    /// assert_eq!(err.position().unwrap().offset, 27);
+    /// ```
+    pub fn from_bytes(input: &[u8]) -> error::PemResult<PemMessage> {
+        let mut blocks = PemBytesBlocks::with_options(input, ParseOptions::default());
+        blocks.next().unwrap_or_else(|| Err(blocks.missing_block()))
+    }
+
+    /// Parse every PEM block in `input`, in order, without requiring UTF-8
+    ///
+    /// See [`from_bytes`](PemMessage::from_bytes) for the handling of
+    /// non-ASCII bytes, and [`PemReader`] to parse a stream block by block.
+    pub fn parse_all_bytes(input: &[u8]) -> error::PemResult<Vec<PemMessage>> {
+        PemBytesBlocks::with_options(input, ParseOptions::default()).collect()
     }
 }
 
 impl FromStr for PemMessage {
     type Err = error::Error;
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        parser::pem_parser(s)
+        parser::pem_parser(s, &ParseOptions::default())
     }
 }
 
@@ -0,0 +1,548 @@
+//! Checks of PEM text against the rules of RFC 7468
+//!
+//! Unlike the parser, [`lint`] does not stop at the first problem: it walks
+//! the whole input and reports every deviation it finds, from plain errors
+//! to layouts which are accepted but not generated by RFC 7468 writers.
+//!
+//! ```
+//! use easypem::lint::{lint, DiagnosticKind, Severity};
+//!
+//! let text = "Subject: CN=Example
+//! -----BEGIN CERTIFICATE-----
+//! Zmlyc3Q
+//! -----END CERTIFICATE-----";
+//!
+//! let diagnostics = lint(text);
+//! let kinds: Vec<_> = diagnostics.iter().map(|d| d.kind).collect();
+//! assert_eq!(kinds, [DiagnosticKind::ExplanatoryText, DiagnosticKind::MissingPadding]);
+//! assert_eq!(diagnostics[1].severity, Severity::Warning);
+//! assert_eq!(diagnostics[1].position.line, 3);
+//! ```
+
+use crate::error::{line_starts, Position};
+use crate::parser::{boundary_label, is_valid_label, EB_SUFFIX, POST_EB_PREFIX, PRE_EB_PREFIX};
+use crate::PemLabel;
+use std::fmt;
+
+/// Number of base64 characters on each full line of RFC 7468 content
+const LINE_WIDTH: usize = 64;
+
+/// How serious a [`Diagnostic`] is
+///
+/// Severities are ordered, so `severity >= Severity::Warning` selects
+/// warnings and errors.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Severity {
+    /// Allowed by RFC 7468, but worth knowing
+    Info,
+    /// Accepted by the parser, but not generated by RFC 7468 writers
+    Warning,
+    /// Rejected by the parser
+    Error,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            Severity::Info => "info",
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+        })
+    }
+}
+
+/// Rule broken by a [`Diagnostic`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum DiagnosticKind {
+    /// A block has no `-----END` line
+    MissingEndBoundary,
+    /// The label of the `-----END` line differs from the `-----BEGIN` one
+    LabelMismatch,
+    /// The label of an encapsulation boundary is malformed
+    InvalidLabel,
+    /// The label is not one of RFC 7468
+    NonStandardLabel,
+    /// An encapsulation boundary does not start at the beginning of its line
+    IndentedBoundary,
+    /// A block has neither headers nor base64 text
+    MissingContent,
+    /// A character of the content is not base64
+    InvalidBase64,
+    /// A base64 line is wider than 64 characters
+    LongLine,
+    /// A base64 line other than the last one is narrower than 64 characters
+    ShortLine,
+    /// A line ends with spaces or tabs
+    TrailingWhitespace,
+    /// The base64 text lacks `=` padding
+    MissingPadding,
+    /// The base64 text has too many `=`, or some in the middle
+    ExcessPadding,
+    /// The unused bits of the last base64 character are not zero
+    NonCanonicalBits,
+    /// A block with an RFC 7468 label has RFC 1421 header fields
+    HeadersInRfc7468Block,
+    /// Text is found outside of the blocks
+    ExplanatoryText,
+    /// Lines end with different line endings
+    MixedLineEndings,
+    /// The input has no block at all
+    MissingBlock,
+}
+
+impl DiagnosticKind {
+    pub fn severity(self) -> Severity {
+        match self {
+            DiagnosticKind::MissingEndBoundary
+            | DiagnosticKind::LabelMismatch
+            | DiagnosticKind::InvalidLabel
+            | DiagnosticKind::IndentedBoundary
+            | DiagnosticKind::MissingContent
+            | DiagnosticKind::InvalidBase64
+            | DiagnosticKind::ExcessPadding
+            | DiagnosticKind::MissingBlock => Severity::Error,
+            DiagnosticKind::NonStandardLabel
+            | DiagnosticKind::LongLine
+            | DiagnosticKind::ShortLine
+            | DiagnosticKind::TrailingWhitespace
+            | DiagnosticKind::MissingPadding
+            | DiagnosticKind::NonCanonicalBits
+            | DiagnosticKind::HeadersInRfc7468Block
+            | DiagnosticKind::MixedLineEndings => Severity::Warning,
+            DiagnosticKind::ExplanatoryText => Severity::Info,
+        }
+    }
+}
+
+/// Problem found by [`lint`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub kind: DiagnosticKind,
+    pub severity: Severity,
+    pub position: Position,
+    pub message: String,
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}: {}: {}", self.position, self.severity, self.message)
+    }
+}
+
+/// Check every block of `input`, returning the problems in the order of
+/// the input
+pub fn lint(input: &str) -> Vec<Diagnostic> {
+    let mut linter = Linter {
+        input,
+        line_starts: std::iter::once(0).chain(line_starts(input)).collect(),
+        diagnostics: Vec::new(),
+    };
+    linter.run();
+    linter.diagnostics.sort_by_key(|d| d.position.offset);
+    linter.diagnostics
+}
+
+/// Problem found in a piece of text, at a byte offset of that text
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Finding {
+    pub kind: DiagnosticKind,
+    pub offset: usize,
+    pub message: String,
+}
+
+impl Finding {
+    fn new<S: Into<String>>(kind: DiagnosticKind, offset: usize, message: S) -> Self {
+        Finding {
+            kind,
+            offset,
+            message: message.into(),
+        }
+    }
+}
+
+/// Check the base64 text of a block, without its boundaries and headers
+///
+/// Reports the line widths, the characters, the padding and the trailing
+/// bits, each at the offset in `text` where the rule is broken.
+pub(crate) fn check_base64(text: &str) -> Vec<Finding> {
+    let mut findings = Vec::new();
+    let lines = split_lines(text)
+        .map(|line| (line.start, line.text.trim_end_matches([' ', '\t'])))
+        .filter(|(_, line)| !line.trim_start().is_empty())
+        .collect::<Vec<_>>();
+    let mut data = Vec::new();
+    let mut padding = Vec::new();
+    let mut misplaced_padding = false;
+
+    for (idx, &(start, line)) in lines.iter().enumerate() {
+        let width = line.chars().count();
+        if width > LINE_WIDTH {
+            let (pos, _) = line.char_indices().nth(LINE_WIDTH).unwrap();
+            findings.push(Finding::new(
+                DiagnosticKind::LongLine,
+                start + pos,
+                format!("Line of {} characters, wider than {}", width, LINE_WIDTH),
+            ));
+        } else if width < LINE_WIDTH && idx + 1 < lines.len() {
+            findings.push(Finding::new(
+                DiagnosticKind::ShortLine,
+                start + line.len(),
+                format!(
+                    "Line of {} characters before the last line, instead of {}",
+                    width, LINE_WIDTH
+                ),
+            ));
+        }
+
+        let mut invalid = false;
+        for (pos, c) in line.char_indices() {
+            let offset = start + pos;
+            match c {
+                '=' => padding.push(offset),
+                ' ' | '\t' | '\x0B' | '\x0C' => (),
+                c if sextet(c).is_some() => {
+                    if !padding.is_empty() && !misplaced_padding {
+                        misplaced_padding = true;
+                        findings.push(Finding::new(
+                            DiagnosticKind::ExcessPadding,
+                            padding[0],
+                            "Padding in the middle of the base64 text",
+                        ));
+                    }
+                    data.push((offset, c));
+                }
+                c if !invalid => {
+                    invalid = true;
+                    findings.push(Finding::new(
+                        DiagnosticKind::InvalidBase64,
+                        offset,
+                        format!("Invalid base64 character {:?}", c),
+                    ));
+                }
+                _ => (),
+            }
+        }
+    }
+
+    let (last, c) = match data.last() {
+        Some(&last) => last,
+        None => return findings,
+    };
+    let remainder = data.len() % 4;
+    if remainder == 1 {
+        findings.push(Finding::new(
+            DiagnosticKind::InvalidBase64,
+            last,
+            "Base64 text ends with a single character in its last quantum",
+        ));
+        return findings;
+    }
+    let needed = (4 - remainder) % 4;
+    if misplaced_padding {
+        // Already reported
+    } else if padding.len() > needed {
+        findings.push(Finding::new(
+            DiagnosticKind::ExcessPadding,
+            padding[needed],
+            format!(
+                "{} padding characters, instead of {}",
+                padding.len(),
+                needed
+            ),
+        ));
+    } else if padding.len() < needed {
+        let end = padding.last().map_or(last + 1, |&pos| pos + 1);
+        findings.push(Finding::new(
+            DiagnosticKind::MissingPadding,
+            end,
+            format!(
+                "{} padding characters, instead of {}",
+                padding.len(),
+                needed
+            ),
+        ));
+    }
+    let unused_bits = match remainder {
+        2 => 0x0F,
+        3 => 0x03,
+        _ => 0,
+    };
+    if sextet(c).unwrap() & unused_bits != 0 {
+        findings.push(Finding::new(
+            DiagnosticKind::NonCanonicalBits,
+            last,
+            format!("Non-zero unused bits in the last base64 character {:?}", c),
+        ));
+    }
+    findings
+}
+
+/// Value of a base64 character
+fn sextet(c: char) -> Option<u8> {
+    match c {
+        'A'..='Z' => Some(c as u8 - b'A'),
+        'a'..='z' => Some(c as u8 - b'a' + 26),
+        '0'..='9' => Some(c as u8 - b'0' + 52),
+        '+' => Some(62),
+        '/' => Some(63),
+        _ => None,
+    }
+}
+
+/// Line of a text, without its line ending
+#[derive(Debug, Clone, Copy)]
+struct Line<'a> {
+    /// Byte offset of the line in the text
+    start: usize,
+    text: &'a str,
+    /// `\n`, `\r\n`, `\r`, or empty for the last line
+    eol: &'a str,
+}
+
+impl Line<'_> {
+    fn end(&self) -> usize {
+        self.start + self.text.len()
+    }
+
+    fn is_blank(&self) -> bool {
+        self.text.trim().is_empty()
+    }
+}
+
+fn split_lines(text: &str) -> impl Iterator<Item = Line<'_>> {
+    let mut start = 0;
+    std::iter::from_fn(move || {
+        if start >= text.len() {
+            return None;
+        }
+        let rest = &text[start..];
+        let len = rest.find(['\r', '\n']).unwrap_or(rest.len());
+        let eol_len = if rest[len..].starts_with("\r\n") {
+            2
+        } else {
+            rest[len..].len().min(1)
+        };
+        let line = Line {
+            start,
+            text: &rest[..len],
+            eol: &rest[len..len + eol_len],
+        };
+        start += len + eol_len;
+        Some(line)
+    })
+}
+
+struct Linter<'a> {
+    input: &'a str,
+    /// Byte offset of the start of each line, as counted by [`Position`]
+    line_starts: Vec<usize>,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl Linter<'_> {
+    fn position(&self, offset: usize) -> Position {
+        let line = self.line_starts.partition_point(|&start| start <= offset);
+        let line_start = self.line_starts[line - 1];
+        Position {
+            line,
+            column: self.input[line_start..offset].chars().count() + 1,
+            offset,
+        }
+    }
+
+    fn report<S: Into<String>>(&mut self, kind: DiagnosticKind, offset: usize, message: S) {
+        self.diagnostics.push(Diagnostic {
+            kind,
+            severity: kind.severity(),
+            position: self.position(offset),
+            message: message.into(),
+        });
+    }
+
+    fn run(&mut self) {
+        let lines = split_lines(self.input).collect::<Vec<_>>();
+        self.check_lines(&lines);
+
+        let mut found_block = false;
+        let mut in_text = false;
+        let mut idx = 0;
+        while idx < lines.len() {
+            let line = lines[idx];
+            if line.text.trim_start().starts_with(PRE_EB_PREFIX) {
+                found_block = true;
+                in_text = false;
+                idx = self.check_block(&lines, idx);
+                continue;
+            }
+            if !line.is_blank() && !in_text {
+                in_text = true;
+                self.report(
+                    DiagnosticKind::ExplanatoryText,
+                    line.start,
+                    "Explanatory text outside of the blocks",
+                );
+            }
+            idx += 1;
+        }
+        if !found_block {
+            self.report(
+                DiagnosticKind::MissingBlock,
+                self.input.len(),
+                "No PEM block found",
+            );
+        }
+    }
+
+    /// Check the line endings and the trailing whitespace of every line
+    fn check_lines(&mut self, lines: &[Line<'_>]) {
+        let mut first_eol = None;
+        let mut mixed = false;
+        for line in lines {
+            let trimmed = line.text.trim_end_matches([' ', '\t']);
+            if trimmed.len() < line.text.len() {
+                self.report(
+                    DiagnosticKind::TrailingWhitespace,
+                    line.start + trimmed.len(),
+                    "Whitespace at the end of the line",
+                );
+            }
+            if line.eol.is_empty() {
+                continue;
+            }
+            match first_eol {
+                None => first_eol = Some(line.eol),
+                // Only the first change of line ending is reported
+                Some(eol) if eol != line.eol && !mixed => {
+                    mixed = true;
+                    let message = format!(
+                        "{} line ending after {} ones",
+                        eol_name(line.eol),
+                        eol_name(eol)
+                    );
+                    self.report(DiagnosticKind::MixedLineEndings, line.end(), message);
+                }
+                Some(_) => (),
+            }
+        }
+    }
+
+    /// Report the boundary `line` if it does not start at column 1
+    fn check_indent(&mut self, line: Line<'_>, indent: usize, boundary: &str) {
+        if indent > 0 {
+            self.report(
+                DiagnosticKind::IndentedBoundary,
+                line.start,
+                format!("{} boundary does not start at column 1", boundary),
+            );
+        }
+    }
+
+    /// Check the block starting at `lines[begin]`, returning the index of
+    /// the line following it
+    fn check_block(&mut self, lines: &[Line<'_>], begin: usize) -> usize {
+        let pre_eb = lines[begin];
+        let indent = pre_eb.text.len() - pre_eb.text.trim_start().len();
+        let label_offset = pre_eb.start + indent + PRE_EB_PREFIX.len();
+        self.check_indent(pre_eb, indent, "Pre-encapsulation");
+        let boundary = pre_eb.text.trim();
+        let label = boundary_label(boundary, PRE_EB_PREFIX);
+        if !boundary.ends_with(EB_SUFFIX) || !is_valid_label(label) {
+            self.report(
+                DiagnosticKind::InvalidLabel,
+                label_offset,
+                "Invalid pre-encapsulation boundary",
+            );
+        } else {
+            let typed = PemLabel::from(label);
+            if !typed.is_rfc7468() {
+                let message = match typed.preferred() {
+                    preferred if preferred != typed => format!(
+                        "Label \"{}\" is not defined by RFC 7468, use \"{}\"",
+                        label, preferred
+                    ),
+                    _ => format!("Label \"{}\" is not defined by RFC 7468", label),
+                };
+                self.report(DiagnosticKind::NonStandardLabel, label_offset, message);
+            }
+        }
+
+        let stop = lines[begin + 1..]
+            .iter()
+            .position(|line| {
+                let text = line.text.trim_start();
+                text.starts_with(POST_EB_PREFIX) || text.starts_with(PRE_EB_PREFIX)
+            })
+            .map_or(lines.len(), |idx| begin + 1 + idx);
+        let end = match lines.get(stop) {
+            Some(line) if line.text.trim_start().starts_with(POST_EB_PREFIX) => stop,
+            _ => {
+                self.report(
+                    DiagnosticKind::MissingEndBoundary,
+                    pre_eb.start + indent,
+                    format!(
+                        "Missing post-encapsulation boundary for label \"{}\"",
+                        label
+                    ),
+                );
+                return stop;
+            }
+        };
+
+        // Headers are the lines up to the first empty one,
+        // when the first line after the boundary is a header field
+        let body = &lines[begin + 1..end];
+        let mut content = 0;
+        if body.first().is_some_and(|line| line.text.contains(':')) {
+            content = body.iter().position(Line::is_blank).unwrap_or(body.len());
+            if PemLabel::from(label).is_rfc7468() {
+                self.report(
+                    DiagnosticKind::HeadersInRfc7468Block,
+                    body[0].start,
+                    format!("Header fields in a \"{}\" block", label),
+                );
+            }
+        }
+        let content_start = body
+            .get(content)
+            .map_or(lines[end].start, |line| line.start);
+        let text = &self.input[content_start..lines[end].start];
+        if text.trim().is_empty() && content == 0 {
+            self.report(
+                DiagnosticKind::MissingContent,
+                lines[end].start,
+                "Missing PEM content",
+            );
+        }
+        for finding in check_base64(text) {
+            self.report(
+                finding.kind,
+                content_start + finding.offset,
+                finding.message,
+            );
+        }
+
+        let post_eb = lines[end];
+        let indent = post_eb.text.len() - post_eb.text.trim_start().len();
+        self.check_indent(post_eb, indent, "Post-encapsulation");
+        if boundary_label(post_eb.text.trim(), POST_EB_PREFIX) != label {
+            self.report(
+                DiagnosticKind::LabelMismatch,
+                post_eb.start + indent + POST_EB_PREFIX.len(),
+                format!(
+                    "Post-encapsulation boundary does not match label \"{}\"",
+                    label
+                ),
+            );
+        }
+        end + 1
+    }
+}
+
+fn eol_name(eol: &str) -> &'static str {
+    match eol {
+        "\r\n" => "CRLF",
+        "\r" => "CR",
+        _ => "LF",
+    }
+}
@@ -0,0 +1,166 @@
+use crate::error::{Error, PemResult, Position};
+use crate::headers::PemHeader;
+use crate::parser::{check_canonical, decode_base64_into, parse_block, RawBlock};
+use crate::{ParseOptions, PemBlocks, PemBuilder, PemMessage};
+
+/// PEM block borrowed from the parsed input
+///
+/// Unlike [`PemMessage`], nothing is allocated: the label, the header fields
+/// and the base64 text are slices of the input, and the content is only
+/// decoded on request. The header section is only located: its fields are
+/// parsed, and rejected if malformed, by [`to_message`](PemRef::to_message).
+///
+/// ```
+/// # use easypem::PemRef;
+/// let bundle = "-----BEGIN FIRST-----
+/// Zmlyc3Q=
+/// -----END FIRST-----
+/// -----BEGIN SECOND-----
+/// c2Vjb25k
+/// -----END SECOND-----
+/// ";
+///
+/// let blocks = PemRef::parse_all(bundle).unwrap();
+/// assert_eq!(blocks[1].label(), "SECOND");
+/// assert_eq!(blocks[1].base64(), "c2Vjb25k");
+/// assert_eq!(blocks[1].decoded_len(), 6);
+///
+/// let mut content = Vec::new();
+/// for pem in &blocks {
+///     pem.decode_into(&mut content).unwrap();
+/// }
+/// assert_eq!(content, b"firstsecond");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PemRef<'a> {
+    /// Whole input, to locate errors
+    input: &'a str,
+    label: &'a str,
+    headers: &'a str,
+    headers_offset: usize,
+    base64: &'a str,
+    base64_offset: usize,
+    explanatory_text: &'a str,
+}
+
+impl<'a> PemRef<'a> {
+    /// Parse the first PEM block in `input`
+    pub fn parse(input: &'a str) -> PemResult<PemRef<'a>> {
+        Self::parse_with(input, &ParseOptions::default())
+    }
+
+    /// Parse the first PEM block in `input` with the given options
+    ///
+    /// [`ParseOptions::lossless`] has no effect, the source text being kept
+    /// anyway.
+    pub fn parse_with(input: &'a str, options: &ParseOptions) -> PemResult<PemRef<'a>> {
+        PemRef::from_raw(&parse_block(input, options)?, input, 0, options)
+    }
+
+    /// Parse every PEM block in `input`, in order
+    ///
+    /// The first block failing to parse aborts the whole operation.
+    pub fn parse_all(input: &'a str) -> PemResult<Vec<PemRef<'a>>> {
+        Self::parse_all_with(input, &ParseOptions::default())
+    }
+
+    /// Parse every PEM block in `input` with the given options
+    pub fn parse_all_with(input: &'a str, options: &ParseOptions) -> PemResult<Vec<PemRef<'a>>> {
+        let mut blocks = PemBlocks::with_options(input, options.clone());
+        std::iter::from_fn(|| blocks.next_borrowed()).collect()
+    }
+
+    /// Borrow the parts of `block`, found in `input[start..]`
+    ///
+    /// Errors are located in `input[start..]`.
+    pub(crate) fn from_raw(
+        block: &RawBlock,
+        input: &'a str,
+        start: usize,
+        options: &ParseOptions,
+    ) -> PemResult<Self> {
+        let text = &input[start..];
+        let headers = block
+            .headers
+            .clone()
+            .unwrap_or(block.pre_eb.end..block.pre_eb.end);
+        let content = block
+            .content
+            .clone()
+            .unwrap_or(block.post_eb.start..block.post_eb.start);
+        if options.canonical_base64 {
+            check_canonical(&text[content.clone()], text, content.start)?;
+        }
+        Ok(PemRef {
+            input,
+            label: &text[block.label.clone()],
+            headers: &text[headers.clone()],
+            headers_offset: start + headers.start,
+            base64: text[content.clone()].trim_end(),
+            base64_offset: start + content.start,
+            explanatory_text: &text[block.explanatory.clone()],
+        })
+    }
+
+    pub fn label(&self) -> &'a str {
+        self.label
+    }
+
+    /// Header fields as found in the input, each line with its line ending,
+    /// or an empty string
+    pub fn headers(&self) -> &'a str {
+        self.headers
+    }
+
+    /// Base64 text as found in the input, without the line ending of its
+    /// last line
+    pub fn base64(&self) -> &'a str {
+        self.base64
+    }
+
+    /// Explanatory text found before the pre-encapsulation boundary
+    pub fn explanatory_text(&self) -> &'a str {
+        self.explanatory_text
+    }
+
+    /// Number of bytes of the decoded content
+    ///
+    /// Computed from the number of base64 characters, it is exact as long as
+    /// the content decodes successfully.
+    pub fn decoded_len(&self) -> usize {
+        let chars = self
+            .base64
+            .bytes()
+            .filter(|b| b.is_ascii_alphanumeric() || matches!(b, b'+' | b'/'))
+            .count();
+        chars / 4 * 3 + (chars % 4).saturating_sub(1)
+    }
+
+    /// Decode the content, appending it to `buf`
+    pub fn decode_into(&self, buf: &mut Vec<u8>) -> PemResult<()> {
+        buf.reserve(self.decoded_len());
+        decode_base64_into(self.base64, buf, self.input, self.base64_offset)
+    }
+
+    /// Parse the header fields and decode the content into an owned message
+    ///
+    /// The [`spans`](PemMessage::spans) of the message are not set, but
+    /// the header fields have their span in the input.
+    pub fn to_message(&self) -> PemResult<PemMessage> {
+        let mut builder = PemBuilder::new()
+            .label(self.label)
+            .explanatory_text(self.explanatory_text);
+        if !self.headers.is_empty() {
+            let mut headers = PemHeader::from_str(self.headers)
+                .map_err(|err| Error::from(err).rebase(self.input, self.headers_offset))?;
+            let base = Position::from_offset(self.input, self.headers_offset);
+            for field in &mut headers.fields {
+                field.rebase_span(base);
+            }
+            builder = builder.headers(headers);
+        }
+        let mut content = Vec::new();
+        self.decode_into(&mut content)?;
+        Ok(builder.content(content).build_parsed())
+    }
+}
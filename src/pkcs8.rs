@@ -0,0 +1,456 @@
+//! PBES2 encryption and decryption of PKCS #8 private keys
+//!
+//! An `ENCRYPTED PRIVATE KEY` message holds an `EncryptedPrivateKeyInfo`
+//! (RFC 5958) whose encryption algorithm is PBES2 (RFC 8018), combining
+//! a key derivation function with a block cipher in CBC mode.
+//!
+//! Supported key derivation functions are PBKDF2 with HMAC-SHA1 or
+//! HMAC-SHA256, and scrypt (RFC 7914). Supported ciphers are DES-CBC,
+//! DES-EDE3-CBC and AES-CBC.
+
+use crate::cipher::crypto_err;
+use crate::der::{self, DerReader};
+use crate::error::{Error, ErrorKind, PemResult};
+use crate::{PemMessage, ENC_PRIVKEY_LABEL, PRIVKEY_LABEL};
+use hmac::Hmac;
+use sha1::Sha1;
+use sha2::Sha256;
+
+pub use crate::cipher::Cipher;
+
+const PBES2_OID: &[u8] = &[0x2a, 0x86, 0x48, 0x86, 0xf7, 0x0d, 0x01, 0x05, 0x0d];
+const PBKDF2_OID: &[u8] = &[0x2a, 0x86, 0x48, 0x86, 0xf7, 0x0d, 0x01, 0x05, 0x0c];
+const SCRYPT_OID: &[u8] = &[0x2b, 0x06, 0x01, 0x04, 0x01, 0xda, 0x47, 0x04, 0x0b];
+const HMAC_SHA1_OID: &[u8] = &[0x2a, 0x86, 0x48, 0x86, 0xf7, 0x0d, 0x02, 0x07];
+const HMAC_SHA256_OID: &[u8] = &[0x2a, 0x86, 0x48, 0x86, 0xf7, 0x0d, 0x02, 0x09];
+const DES_CBC_OID: &[u8] = &[0x2b, 0x0e, 0x03, 0x02, 0x07];
+const DES_EDE3_CBC_OID: &[u8] = &[0x2a, 0x86, 0x48, 0x86, 0xf7, 0x0d, 0x03, 0x07];
+const AES128_CBC_OID: &[u8] = &[0x60, 0x86, 0x48, 0x01, 0x65, 0x03, 0x04, 0x01, 0x02];
+const AES192_CBC_OID: &[u8] = &[0x60, 0x86, 0x48, 0x01, 0x65, 0x03, 0x04, 0x01, 0x16];
+const AES256_CBC_OID: &[u8] = &[0x60, 0x86, 0x48, 0x01, 0x65, 0x03, 0x04, 0x01, 0x2a];
+
+const SALT_LEN: usize = 16;
+
+/// Pseudorandom function used by PBKDF2
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Prf {
+    HmacSha1,
+    HmacSha256,
+}
+
+/// Key derivation function of PBES2, with its parameters
+///
+/// A random salt is generated for each encryption.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Kdf {
+    Pbkdf2 {
+        prf: Prf,
+        iterations: u32,
+    },
+    Scrypt {
+        /// Base 2 logarithm of the cost parameter N
+        log_n: u8,
+        /// Block size
+        r: u32,
+        /// Parallelization parameter
+        p: u32,
+    },
+}
+
+impl Kdf {
+    fn derive(self, passphrase: &[u8], salt: &[u8], key_len: usize) -> PemResult<Vec<u8>> {
+        let mut key = vec![0; key_len];
+        match self {
+            Kdf::Pbkdf2 { prf, iterations } => {
+                let result = match prf {
+                    Prf::HmacSha1 => {
+                        pbkdf2::pbkdf2::<Hmac<Sha1>>(passphrase, salt, iterations, &mut key)
+                    }
+                    Prf::HmacSha256 => {
+                        pbkdf2::pbkdf2::<Hmac<Sha256>>(passphrase, salt, iterations, &mut key)
+                    }
+                };
+                result.map_err(|err| crypto_err(err.to_string()))?;
+            }
+            Kdf::Scrypt { log_n, r, p } => {
+                let params = scrypt::Params::new(log_n, r, p, key_len)
+                    .map_err(|err| crypto_err(format!("Invalid scrypt parameters: {}", err)))?;
+                scrypt::scrypt(passphrase, salt, &params, &mut key)
+                    .map_err(|err| crypto_err(err.to_string()))?;
+            }
+        }
+        Ok(key)
+    }
+
+    /// Append the KDF `AlgorithmIdentifier` to `out`
+    fn write_algorithm(self, out: &mut Vec<u8>, salt: &[u8]) {
+        let mut params = Vec::new();
+        der::write_tlv(&mut params, der::OCTET_STRING, salt);
+        let oid = match self {
+            Kdf::Pbkdf2 { prf, iterations } => {
+                der::write_uint(&mut params, iterations.into());
+                // hmacWithSHA1 is the default and is left out
+                if prf == Prf::HmacSha256 {
+                    let mut prf_algorithm = Vec::new();
+                    der::write_tlv(&mut prf_algorithm, der::OID, HMAC_SHA256_OID);
+                    der::write_tlv(&mut prf_algorithm, der::NULL, &[]);
+                    der::write_tlv(&mut params, der::SEQUENCE, &prf_algorithm);
+                }
+                PBKDF2_OID
+            }
+            Kdf::Scrypt { log_n, r, p } => {
+                der::write_uint(&mut params, 1u64 << log_n);
+                der::write_uint(&mut params, r.into());
+                der::write_uint(&mut params, p.into());
+                SCRYPT_OID
+            }
+        };
+        let mut algorithm = Vec::new();
+        der::write_tlv(&mut algorithm, der::OID, oid);
+        der::write_tlv(&mut algorithm, der::SEQUENCE, &params);
+        der::write_tlv(out, der::SEQUENCE, &algorithm);
+    }
+}
+
+/// Options for [`encrypt_pkcs8`](PemMessage::encrypt_pkcs8)
+///
+/// The default matches `openssl pkcs8 -topk8`: PBKDF2 with HMAC-SHA256
+/// and 2048 iterations, and AES-256-CBC.
+///
+/// ```
+/// # use easypem::pkcs8::{Cipher, EncryptOptions, Kdf};
+/// let options = EncryptOptions {
+///     kdf: Kdf::Scrypt { log_n: 14, r: 8, p: 1 },
+///     cipher: Cipher::Aes128Cbc,
+/// };
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EncryptOptions {
+    pub kdf: Kdf,
+    pub cipher: Cipher,
+}
+
+impl Default for EncryptOptions {
+    fn default() -> Self {
+        EncryptOptions {
+            kdf: Kdf::Pbkdf2 {
+                prf: Prf::HmacSha256,
+                iterations: 2048,
+            },
+            cipher: Cipher::Aes256Cbc,
+        }
+    }
+}
+
+/// Bounds on the cost of the key derivation when decrypting
+///
+/// The KDF parameters come from the encrypted key itself, so a crafted key
+/// could otherwise demand gigabytes of memory or hours of computation before
+/// the passphrase is even checked. The default allows up to 10,000,000
+/// PBKDF2 iterations and 32 MiB of memory for scrypt, like OpenSSL.
+///
+/// ```
+/// # use easypem::pkcs8::DecryptLimits;
+/// let limits = DecryptLimits {
+///     max_scrypt_memory: 256 * 1024 * 1024,
+///     ..Default::default()
+/// };
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DecryptLimits {
+    /// Maximum iteration count of PBKDF2
+    pub max_pbkdf2_iterations: u32,
+    /// Maximum memory used by scrypt, in bytes
+    pub max_scrypt_memory: u64,
+}
+
+impl Default for DecryptLimits {
+    fn default() -> Self {
+        DecryptLimits {
+            max_pbkdf2_iterations: 10_000_000,
+            max_scrypt_memory: 32 * 1024 * 1024,
+        }
+    }
+}
+
+impl DecryptLimits {
+    /// Reject `kdf` if deriving a key with it exceeds the limits
+    fn check(&self, kdf: Kdf) -> PemResult<()> {
+        match kdf {
+            Kdf::Pbkdf2 { iterations, .. } if iterations > self.max_pbkdf2_iterations => {
+                Err(limit_err(format!(
+                    "PBKDF2 iteration count {} exceeds the limit of {}",
+                    iterations, self.max_pbkdf2_iterations
+                )))
+            }
+            Kdf::Scrypt { log_n, r, p } => {
+                // 128 * r * (N + p) bytes for the V and B arrays
+                let memory = (1u128 << log_n)
+                    .saturating_add(p.into())
+                    .saturating_mul(128 * u128::from(r));
+                if memory > self.max_scrypt_memory.into() {
+                    return Err(limit_err(format!(
+                        "scrypt parameters need {} bytes of memory, over the limit of {}",
+                        memory, self.max_scrypt_memory
+                    )));
+                }
+                Ok(())
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+fn limit_err(message: String) -> Error {
+    Error::new(ErrorKind::LimitExceeded, message)
+}
+
+/// Parameters of the PBES2 encryption scheme
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Pbes2<'a> {
+    kdf: Kdf,
+    salt: &'a [u8],
+    cipher: Cipher,
+    iv: &'a [u8],
+}
+
+impl PemMessage {
+    /// Decrypt an `ENCRYPTED PRIVATE KEY` message into a `PRIVATE KEY` message
+    ///
+    /// The key derivation is bounded by the default [`DecryptLimits`].
+    ///
+    /// # Errors
+    /// Fail if the label is not `ENCRYPTED PRIVATE KEY`, if the encryption
+    /// scheme is not supported, or if the passphrase is wrong.
+    pub fn decrypt_pkcs8(&self, passphrase: &[u8]) -> PemResult<PemMessage> {
+        self.decrypt_pkcs8_with(passphrase, &DecryptLimits::default())
+    }
+
+    /// Decrypt an `ENCRYPTED PRIVATE KEY` message with the given limits on
+    /// the key derivation
+    ///
+    /// # Errors
+    /// Same as [`decrypt_pkcs8`](PemMessage::decrypt_pkcs8), and fail with
+    /// [`ErrorKind::LimitExceeded`] if the KDF parameters exceed `limits`.
+    pub fn decrypt_pkcs8_with(
+        &self,
+        passphrase: &[u8],
+        limits: &DecryptLimits,
+    ) -> PemResult<PemMessage> {
+        if self.label != ENC_PRIVKEY_LABEL {
+            return Err(crypto_err(format!(
+                "Expected label \"{}\", found \"{}\"",
+                ENC_PRIVKEY_LABEL, &self.label
+            )));
+        }
+        let (scheme, encrypted) = parse_encrypted_private_key_info(&self.content)?;
+        limits.check(scheme.kdf)?;
+
+        let key = scheme
+            .kdf
+            .derive(passphrase, scheme.salt, scheme.cipher.key_len())?;
+        let content = scheme
+            .cipher
+            .decrypt(&key, scheme.iv, encrypted)
+            .filter(|content| is_single_sequence(content))
+            .ok_or_else(|| crypto_err("Bad decrypt, the passphrase may be wrong"))?;
+        Ok(PemMessage {
+            label: PRIVKEY_LABEL.to_owned(),
+            content,
+            ..Default::default()
+        })
+    }
+
+    /// Encrypt a `PRIVATE KEY` message into an `ENCRYPTED PRIVATE KEY` message,
+    /// with a random salt and IV
+    ///
+    /// ```
+    /// # use easypem::{PemMessage, pkcs8::EncryptOptions};
+    /// let key = PemMessage::new("PRIVATE KEY", vec![0x30, 0x03, 0x02, 0x01, 0x00]);
+    ///
+    /// let encrypted = key.encrypt_pkcs8(b"secret", &EncryptOptions::default()).unwrap();
+    /// assert_eq!(encrypted.label, "ENCRYPTED PRIVATE KEY");
+    /// assert_eq!(encrypted.decrypt_pkcs8(b"secret").unwrap(), key);
+    /// ```
+    ///
+    /// # Errors
+    /// Fail if the label is not `PRIVATE KEY`, or if the KDF parameters are invalid.
+    pub fn encrypt_pkcs8(
+        &self,
+        passphrase: &[u8],
+        options: &EncryptOptions,
+    ) -> PemResult<PemMessage> {
+        let mut salt = [0; SALT_LEN];
+        let mut iv = vec![0; options.cipher.iv_len()];
+        getrandom::getrandom(&mut salt).map_err(|err| crypto_err(err.to_string()))?;
+        getrandom::getrandom(&mut iv).map_err(|err| crypto_err(err.to_string()))?;
+        self.encrypt_pkcs8_with_salt_iv(passphrase, options, &salt, &iv)
+    }
+
+    /// Encrypt a `PRIVATE KEY` message with the given salt and IV
+    ///
+    /// The salt and IV must never be reused with the same passphrase,
+    /// prefer [`encrypt_pkcs8`](PemMessage::encrypt_pkcs8).
+    pub fn encrypt_pkcs8_with_salt_iv(
+        &self,
+        passphrase: &[u8],
+        options: &EncryptOptions,
+        salt: &[u8],
+        iv: &[u8],
+    ) -> PemResult<PemMessage> {
+        if self.label != PRIVKEY_LABEL {
+            return Err(crypto_err(format!(
+                "Expected label \"{}\", found \"{}\"",
+                PRIVKEY_LABEL, &self.label
+            )));
+        }
+        let cipher = options.cipher;
+        if iv.len() != cipher.iv_len() {
+            return Err(crypto_err(format!("Invalid IV length for {}", cipher)));
+        }
+
+        let key = options.kdf.derive(passphrase, salt, cipher.key_len())?;
+        let encrypted = cipher.encrypt(&key, iv, &self.content);
+
+        let mut enc_algorithm = Vec::new();
+        der::write_tlv(&mut enc_algorithm, der::OID, cipher_oid(cipher));
+        der::write_tlv(&mut enc_algorithm, der::OCTET_STRING, iv);
+        let mut params = Vec::new();
+        options.kdf.write_algorithm(&mut params, salt);
+        der::write_tlv(&mut params, der::SEQUENCE, &enc_algorithm);
+        let mut algorithm = Vec::new();
+        der::write_tlv(&mut algorithm, der::OID, PBES2_OID);
+        der::write_tlv(&mut algorithm, der::SEQUENCE, &params);
+        let mut info = Vec::new();
+        der::write_tlv(&mut info, der::SEQUENCE, &algorithm);
+        der::write_tlv(&mut info, der::OCTET_STRING, &encrypted);
+        let mut content = Vec::new();
+        der::write_tlv(&mut content, der::SEQUENCE, &info);
+
+        Ok(PemMessage {
+            label: ENC_PRIVKEY_LABEL.to_owned(),
+            content,
+            ..Default::default()
+        })
+    }
+}
+
+/// Check that `data` is exactly one DER SEQUENCE,
+/// to detect a wrong passphrase which still yields a valid padding
+fn is_single_sequence(data: &[u8]) -> bool {
+    let mut reader = DerReader::new(data);
+    reader.read(der::SEQUENCE).is_some() && reader.is_empty()
+}
+
+fn malformed() -> crate::error::Error {
+    crypto_err("Malformed EncryptedPrivateKeyInfo")
+}
+
+/// Parse an `EncryptedPrivateKeyInfo`,
+/// returning the encryption scheme and the encrypted data
+fn parse_encrypted_private_key_info(data: &[u8]) -> PemResult<(Pbes2<'_>, &[u8])> {
+    let mut outer = DerReader::new(data);
+    let mut info = DerReader::new(outer.read(der::SEQUENCE).ok_or_else(malformed)?);
+    let mut algorithm = DerReader::new(info.read(der::SEQUENCE).ok_or_else(malformed)?);
+    let encrypted = info.read(der::OCTET_STRING).ok_or_else(malformed)?;
+
+    if algorithm.read(der::OID).ok_or_else(malformed)? != PBES2_OID {
+        return Err(crypto_err(
+            "Unsupported encryption scheme, only PBES2 is supported",
+        ));
+    }
+    let mut params = DerReader::new(algorithm.read(der::SEQUENCE).ok_or_else(malformed)?);
+    let mut kdf_algorithm = DerReader::new(params.read(der::SEQUENCE).ok_or_else(malformed)?);
+    let mut enc_algorithm = DerReader::new(params.read(der::SEQUENCE).ok_or_else(malformed)?);
+
+    let cipher_oid = enc_algorithm.read(der::OID).ok_or_else(malformed)?;
+    let cipher = match cipher_oid {
+        DES_CBC_OID => Cipher::DesCbc,
+        DES_EDE3_CBC_OID => Cipher::DesEde3Cbc,
+        AES128_CBC_OID => Cipher::Aes128Cbc,
+        AES192_CBC_OID => Cipher::Aes192Cbc,
+        AES256_CBC_OID => Cipher::Aes256Cbc,
+        _ => return Err(crypto_err("Unsupported PBES2 cipher")),
+    };
+    let iv = enc_algorithm
+        .read(der::OCTET_STRING)
+        .ok_or_else(malformed)?;
+    if iv.len() != cipher.iv_len() {
+        return Err(crypto_err(format!("Invalid IV length for {}", cipher)));
+    }
+
+    let kdf_oid = kdf_algorithm.read(der::OID).ok_or_else(malformed)?;
+    let mut kdf_params = DerReader::new(kdf_algorithm.read(der::SEQUENCE).ok_or_else(malformed)?);
+    let salt = kdf_params.read(der::OCTET_STRING).ok_or_else(malformed)?;
+    let kdf = match kdf_oid {
+        PBKDF2_OID => {
+            let iterations = read_u32(&mut kdf_params)?;
+            let key_len = kdf_params.read_uint();
+            let prf = match kdf_params.read(der::SEQUENCE) {
+                None => Prf::HmacSha1,
+                Some(prf_algorithm) => {
+                    let mut prf_algorithm = DerReader::new(prf_algorithm);
+                    match prf_algorithm.read(der::OID).ok_or_else(malformed)? {
+                        HMAC_SHA1_OID => Prf::HmacSha1,
+                        HMAC_SHA256_OID => Prf::HmacSha256,
+                        _ => return Err(crypto_err("Unsupported PBKDF2 pseudorandom function")),
+                    }
+                }
+            };
+            check_key_len(key_len, cipher)?;
+            Kdf::Pbkdf2 { prf, iterations }
+        }
+        SCRYPT_OID => {
+            let cost = kdf_params.read_uint().ok_or_else(malformed)?;
+            if !cost.is_power_of_two() || cost < 2 {
+                return Err(crypto_err("Invalid scrypt cost parameter"));
+            }
+            let r = read_u32(&mut kdf_params)?;
+            let p = read_u32(&mut kdf_params)?;
+            check_key_len(kdf_params.read_uint(), cipher)?;
+            Kdf::Scrypt {
+                log_n: cost.trailing_zeros() as u8,
+                r,
+                p,
+            }
+        }
+        _ => return Err(crypto_err("Unsupported PBES2 key derivation function")),
+    };
+
+    Ok((
+        Pbes2 {
+            kdf,
+            salt,
+            cipher,
+            iv,
+        },
+        encrypted,
+    ))
+}
+
+fn cipher_oid(cipher: Cipher) -> &'static [u8] {
+    match cipher {
+        Cipher::DesCbc => DES_CBC_OID,
+        Cipher::DesEde3Cbc => DES_EDE3_CBC_OID,
+        Cipher::Aes128Cbc => AES128_CBC_OID,
+        Cipher::Aes192Cbc => AES192_CBC_OID,
+        Cipher::Aes256Cbc => AES256_CBC_OID,
+    }
+}
+
+fn read_u32(reader: &mut DerReader) -> PemResult<u32> {
+    reader
+        .read_uint()
+        .filter(|&n| n <= u32::MAX as u64)
+        .map(|n| n as u32)
+        .ok_or_else(malformed)
+}
+
+fn check_key_len(key_len: Option<u64>, cipher: Cipher) -> PemResult<()> {
+    match key_len {
+        Some(len) if len != cipher.key_len() as u64 => {
+            Err(crypto_err(format!("Invalid key length for {}", cipher)))
+        }
+        _ => Ok(()),
+    }
+}
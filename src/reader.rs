@@ -0,0 +1,256 @@
+use crate::error::{PemResult, Position};
+use crate::parser::{rebase_spans, PemBytesBlocks};
+use crate::{ParseOptions, PemMessage};
+use std::io::{self, BufRead};
+
+const PRE_EB_PREFIX: &[u8] = b"-----BEGIN ";
+const POST_EB_PREFIX: &[u8] = b"-----END ";
+const EB_SUFFIX: &[u8] = b"-----";
+
+/// Streaming PEM parser over a [`BufRead`]
+///
+/// Blocks are read one at a time: the lines of a block are collected up to
+/// its `-----END` line, and parsed like
+/// [`PemMessage::parse_all_bytes`] does, so only a single block is kept in
+/// memory. The lines between the blocks are kept as the explanatory text of
+/// the following block.
+///
+/// The reader accepts and rejects the same inputs as
+/// [`parse_all_bytes`](PemMessage::parse_all_bytes), with errors of the same
+/// kinds, located in the stream. After a broken block, it resumes on the
+/// next `-----BEGIN` line.
+///
+/// ```
+/// # use easypem::PemReader;
+/// let bundle = b"-----BEGIN FIRST-----
+/// Zmlyc3Q=
+/// -----END FIRST-----
+/// -----BEGIN SECOND-----
+/// c2Vjb25k
+/// -----END SECOND-----
+/// ";
+///
+/// let mut reader = PemReader::new(&bundle[..]);
+/// assert_eq!(reader.next().unwrap().unwrap().content, b"first");
+/// assert_eq!(reader.next().unwrap().unwrap().content, b"second");
+/// assert!(reader.next().is_none());
+/// ```
+#[derive(Debug)]
+pub struct PemReader<R> {
+    inner: R,
+    line: Vec<u8>,
+    /// Line ending stripped from `line`
+    eol: &'static str,
+    line_no: usize,
+    /// Byte offset of the current line in the stream
+    line_offset: usize,
+    /// Number of bytes read so far
+    consumed: usize,
+    /// Text following the `-----END` line of the previous block on its line,
+    /// with its position in the stream
+    rest: Option<(Vec<u8>, Position)>,
+    /// The current line is a `-----BEGIN` line left by the previous block
+    resume: bool,
+    /// Drop the lines up to the next `-----BEGIN` line, after a broken block
+    skip: bool,
+    done: bool,
+}
+
+impl<R: BufRead> PemReader<R> {
+    pub fn new(inner: R) -> Self {
+        PemReader {
+            inner,
+            line: Vec::new(),
+            eol: "",
+            line_no: 0,
+            line_offset: 0,
+            consumed: 0,
+            rest: None,
+            resume: false,
+            skip: false,
+            done: false,
+        }
+    }
+
+    /// Unwrap this `PemReader`, returning the underlying reader
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+
+    /// Read the next line into `self.line`, without the line ending
+    ///
+    /// Like the grammar, `\r\n`, `\n` and a lone `\r` end a line.
+    /// Return `false` on EOF
+    fn read_line(&mut self) -> PemResult<bool> {
+        self.line.clear();
+        self.eol = "";
+        let mut len = 0;
+        loop {
+            let buf = fill_buf(&mut self.inner)?;
+            if buf.is_empty() {
+                break;
+            }
+            match buf.iter().position(|&c| c == b'\n' || c == b'\r') {
+                Some(idx) => {
+                    self.line.extend_from_slice(&buf[..idx]);
+                    self.eol = if buf[idx] == b'\r' { "\r" } else { "\n" };
+                    self.inner.consume(idx + 1);
+                    len += idx + 1;
+                    if self.eol == "\r" && fill_buf(&mut self.inner)?.first() == Some(&b'\n') {
+                        self.inner.consume(1);
+                        len += 1;
+                        self.eol = "\r\n";
+                    }
+                    break;
+                }
+                None => {
+                    let read = buf.len();
+                    self.line.extend_from_slice(buf);
+                    self.inner.consume(read);
+                    len += read;
+                }
+            }
+        }
+        if len == 0 {
+            return Ok(false);
+        }
+        self.line_no += 1;
+        self.line_offset = self.consumed;
+        self.consumed += len;
+        Ok(true)
+    }
+
+    /// Position of the start of the current line
+    fn line_position(&self) -> Position {
+        Position {
+            line: self.line_no,
+            column: 1,
+            offset: self.line_offset,
+        }
+    }
+
+    /// Append the current line and its line ending to `text`
+    fn push_line(&self, text: &mut Vec<u8>) {
+        text.extend_from_slice(&self.line);
+        text.extend_from_slice(self.eol.as_bytes());
+    }
+
+    /// Collect the text of the next block, from its explanatory text up to
+    /// its `-----END` line, the next `-----BEGIN` line or EOF
+    ///
+    /// Return `None` if there is no block left.
+    fn read_block_text(&mut self) -> PemResult<Option<(Vec<u8>, Position)>> {
+        let (mut text, mut start) = self
+            .rest
+            .take()
+            .unwrap_or_else(|| (Vec::new(), self.line_position()));
+        while !std::mem::replace(&mut self.resume, false) {
+            if !self.read_line()? {
+                return Ok(None);
+            }
+            if is_pre_eb(&self.line) {
+                break;
+            }
+            if text.is_empty() {
+                start = self.line_position();
+            }
+            if !self.skip {
+                self.push_line(&mut text);
+            }
+        }
+        self.skip = false;
+        if text.is_empty() {
+            start = self.line_position();
+        }
+        self.push_line(&mut text);
+
+        while self.read_line()? {
+            if is_pre_eb(&self.line) {
+                self.resume = true;
+                break;
+            }
+            self.push_line(&mut text);
+            if self.line.starts_with(POST_EB_PREFIX) {
+                break;
+            }
+        }
+        Ok(Some((text, start)))
+    }
+
+    /// Keep the text following the `-----END` line of `pem` on its line,
+    /// which is the current line, for the next block
+    fn keep_rest(&mut self, pem: &PemMessage) {
+        let post_eb_len = POST_EB_PREFIX.len() + pem.label.len() + EB_SUFFIX.len();
+        if self.line.len() > post_eb_len {
+            let mut rest = self.line[post_eb_len..].to_vec();
+            rest.extend_from_slice(self.eol.as_bytes());
+            let position = Position {
+                line: self.line_no,
+                column: post_eb_len + 1,
+                offset: self.line_offset + post_eb_len,
+            };
+            self.rest = Some((rest, position));
+        }
+    }
+}
+
+impl<R: BufRead> Iterator for PemReader<R> {
+    type Item = PemResult<PemMessage>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let (text, start) = match self.read_block_text() {
+            Ok(Some(block)) => block,
+            Ok(None) => {
+                self.done = true;
+                return None;
+            }
+            Err(err) => {
+                self.done = true;
+                return Some(Err(err));
+            }
+        };
+
+        let result = PemBytesBlocks::with_options(&text, ParseOptions::default())
+            .next()
+            .expect("the text holds a `-----BEGIN` line");
+        Some(match result {
+            Ok(mut pem) => {
+                rebase_spans(&mut pem, start);
+                self.keep_rest(&pem);
+                Ok(pem)
+            }
+            Err(err) => {
+                // Like the parser, resume on the next `-----BEGIN` line
+                self.skip = !self.resume;
+                Err(match err.position() {
+                    Some(position) => err.at(position.rebase(start)),
+                    None => err,
+                })
+            }
+        })
+    }
+}
+
+/// Whether `line` is a `-----BEGIN` line, only preceded by whitespace
+fn is_pre_eb(line: &[u8]) -> bool {
+    let indent = line
+        .iter()
+        .take_while(|&&c| c == b' ' || c == b'\t')
+        .count();
+    line[indent..].starts_with(PRE_EB_PREFIX)
+}
+
+/// `BufRead::fill_buf`, retrying when interrupted
+fn fill_buf<R: BufRead>(reader: &mut R) -> io::Result<&[u8]> {
+    loop {
+        match reader.fill_buf() {
+            Err(err) if err.kind() == io::ErrorKind::Interrupted => continue,
+            // Reborrow to return the buffer out of the loop
+            Ok(_) => return reader.fill_buf(),
+            Err(err) => return Err(err),
+        }
+    }
+}
@@ -0,0 +1,191 @@
+//! Hand-written parser for the common PEM blocks
+//!
+//! [`scan_block`] follows the `pem` rule of `pem.pest` for blocks without
+//! header fields, which covers nearly every certificate and key found in the
+//! wild, byte by byte and without building a token queue. Anything else,
+//! including every malformed block, is left to the grammar, which also
+//! produces the errors.
+
+use crate::parser::{RawBlock, EB_SUFFIX, POST_EB_PREFIX, PRE_EB_PREFIX};
+
+/// `label_char` of the grammar
+fn is_label_char(b: u8) -> bool {
+    matches!(b, b'!'..=b',' | b'.'..=b'~')
+}
+
+/// `base64_char` of the grammar
+fn is_base64_char(b: u8) -> bool {
+    b.is_ascii_alphanumeric() || b == b'+' || b == b'/'
+}
+
+/// `space_char` of the grammar
+fn is_space(b: u8) -> bool {
+    b == b' ' || b == b'\t'
+}
+
+/// Length of the `NEWLINE` at the start of `input`, if any
+fn newline_len(input: &[u8]) -> Option<usize> {
+    match input {
+        [b'\n', ..] => Some(1),
+        [b'\r', b'\n', ..] => Some(2),
+        [b'\r', ..] => Some(1),
+        _ => None,
+    }
+}
+
+/// Count the bytes from `pos` matching `pred`
+fn count(input: &[u8], pos: usize, pred: impl Fn(u8) -> bool) -> usize {
+    input[pos..].iter().take_while(|&&b| pred(b)).count()
+}
+
+/// Find the parts of the first block of `input` as the `pem` rule would,
+/// or `None` if the block has header fields or does not match the rule
+pub(crate) fn scan_block(input: &str) -> Option<RawBlock> {
+    let bytes = input.as_bytes();
+
+    // explanatory = { (!(spaces ~ "-----BEGIN ") ~ (!NEWLINE ~ ANY)* ~ NEWLINE)* }
+    // An indented `-----BEGIN ` line is an error left to the grammar
+    let mut pos = 0;
+    while !bytes[pos..].starts_with(PRE_EB_PREFIX.as_bytes()) {
+        let indent = count(bytes, pos, is_space);
+        if indent > 0 && bytes[pos + indent..].starts_with(PRE_EB_PREFIX.as_bytes()) {
+            return None;
+        }
+        let eol = pos
+            + bytes[pos..]
+                .iter()
+                .position(|&b| b == b'\n' || b == b'\r')?;
+        pos = eol + newline_len(&bytes[eol..])?;
+    }
+    let explanatory = 0..pos;
+
+    // pre_eb = { "-----BEGIN " ~ PUSH(label) ~ "-----" }
+    let pre_eb_start = pos;
+    pos += PRE_EB_PREFIX.len();
+    let label = pos..scan_label(bytes, pos)?;
+    pos = label.end;
+    if !bytes[pos..].starts_with(EB_SUFFIX.as_bytes()) {
+        return None;
+    }
+    pos += EB_SUFFIX.len();
+    let pre_eb = pre_eb_start..pos;
+    pos += count(bytes, pos, is_space);
+    pos += newline_len(&bytes[pos..])?;
+
+    // content = { base64_char+ ~ (spaces ~ NEWLINE ~ base64_char+)* ~ "="{, 2} ~ spaces }
+    // A first line which is not base64 may be a header field
+    let content_start = pos;
+    loop {
+        let len = count(bytes, pos, is_base64_char);
+        if len == 0 {
+            return None;
+        }
+        pos += len;
+        let next = pos + count(bytes, pos, is_space);
+        match newline_len(&bytes[next..]) {
+            Some(eol) if bytes.get(next + eol).copied().is_some_and(is_base64_char) => {
+                pos = next + eol;
+            }
+            _ => break,
+        }
+    }
+    pos += count(bytes, pos, |b| b == b'=').min(2);
+    pos += count(bytes, pos, is_space);
+    let content = content_start..pos;
+    pos += newline_len(&bytes[pos..])?;
+
+    // post_eb = { "-----END " ~ POP ~ "-----" }
+    let post_eb_start = pos;
+    let rest = &bytes[pos..];
+    let label_bytes = &bytes[label.clone()];
+    if !(rest.starts_with(POST_EB_PREFIX.as_bytes())
+        && rest[POST_EB_PREFIX.len()..].starts_with(label_bytes)
+        && rest[POST_EB_PREFIX.len() + label_bytes.len()..].starts_with(EB_SUFFIX.as_bytes()))
+    {
+        return None;
+    }
+    pos += POST_EB_PREFIX.len() + label_bytes.len() + EB_SUFFIX.len();
+
+    Some(RawBlock {
+        explanatory,
+        label,
+        pre_eb,
+        headers: None,
+        content: Some(content),
+        post_eb: post_eb_start..pos,
+    })
+}
+
+/// End of the label starting at `pos`
+///
+/// label = { label_char+ ~ (("-" | " ") ~ label_char+)* }
+fn scan_label(bytes: &[u8], mut pos: usize) -> Option<usize> {
+    let len = count(bytes, pos, is_label_char);
+    if len == 0 {
+        return None;
+    }
+    pos += len;
+    while let [b'-' | b' ', next, ..] = bytes[pos..] {
+        if !is_label_char(next) {
+            break;
+        }
+        pos += 1 + count(bytes, pos + 1, is_label_char);
+    }
+    Some(pos)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::parser::{parse_grammar_block, ParseOptions};
+
+    fn grammar_block(input: &str) -> Option<RawBlock> {
+        parse_grammar_block(input, &ParseOptions::default()).ok()
+    }
+
+    #[test]
+    fn scan_common_blocks() {
+        let cases = [
+            "-----BEGIN CERTIFICATE-----\nZm9v\n-----END CERTIFICATE-----",
+            "# Issuer: CN=Example\r\n\r\n-----BEGIN X509 CRL-----  \r\nZm9v \r\nYg==\t\r\n-----END X509 CRL-----\r\n",
+            "Text -----BEGIN A-----\n-----BEGIN A-B C-----\rZm9vYmFy\rYmE=\r-----END A-B C-----trailing",
+            "-----BEGIN A-----\nZm9v\nYg==\n-----END A-----\n-----BEGIN B-----\nYmFy\n-----END B-----",
+            "\u{e9}t\u{e9}\n-----BEGIN A-----\nZg\n-----END A-----",
+        ];
+        for input in cases.iter() {
+            let scanned = scan_block(input);
+            assert!(scanned.is_some(), "{:?}", input);
+            assert_eq!(scanned, grammar_block(input), "{:?}", input);
+        }
+    }
+
+    #[test]
+    fn leave_other_blocks_to_grammar() {
+        let cases = [
+            // Valid for the grammar
+            "-----BEGIN A-----\nComment: x\n\nZm9v\n-----END A-----",
+            "-----BEGIN A-----\nProc-Type: 4,CRL\n-----END A-----",
+            // Errors
+            "",
+            "no block",
+            "-----BEGIN A------\nZm9v\n-----END A-----",
+            "-----BEGIN A--B-----\nZm9v\n-----END A--B-----",
+            "-----BEGIN A-----\nZm9v\n-----END B-----",
+            "-----BEGIN A-----\nZm9v\n-----END A----",
+            "-----BEGIN A-----\n\nZm9v\n-----END A-----",
+            "-----BEGIN A-----\nZm9v\n\n-----END A-----",
+            "-----BEGIN A-----\n Zm9v\n-----END A-----",
+            "-----BEGIN A-----\nZg==\nZm9v\n-----END A-----",
+            "-----BEGIN A-----\nZg===\n-----END A-----",
+            "-----BEGIN A-----\nZm*v\n-----END A-----",
+            "-----BEGIN A-----\nZm9v\nab-----END A-----",
+            "-----BEGIN A-----\n-----END A-----",
+            "-----BEGIN A-----\nZm9v",
+            " -----BEGIN A-----\nZm9v\n-----END A-----",
+            " -----BEGIN A-----\n-----BEGIN B-----\nZm9v\n-----END B-----",
+        ];
+        for input in cases.iter() {
+            assert_eq!(scan_block(input), None, "{:?}", input);
+        }
+    }
+}
@@ -0,0 +1,110 @@
+//! Serde support, enabled by the `serde` feature
+//!
+//! [`PemMessage`] is (de)serialized as its PEM text, while the header types
+//! of [`headers`](crate::headers) are (de)serialized as structures.
+//!
+//! DER encoded fields can be kept as PEM text with one of the modules below
+//! and the `with` attribute:
+//!
+//! ```
+//! # use serde_derive::{Deserialize, Serialize};
+//! #[derive(Serialize, Deserialize)]
+//! struct Config {
+//!     #[serde(with = "easypem::serde::certificate")]
+//!     certificate: Vec<u8>,
+//! }
+//!
+//! let config = Config {
+//!     certificate: vec![0x30, 0x03, 0x02, 0x01, 0x01],
+//! };
+//! let json = serde_json::to_string(&config).unwrap();
+//! assert_eq!(
+//!     json,
+//!     r#"{"certificate":"-----BEGIN CERTIFICATE-----\nMAMCAQE=\n-----END CERTIFICATE-----"}"#
+//! );
+//!
+//! let config: Config = serde_json::from_str(&json).unwrap();
+//! assert_eq!(config.certificate, [0x30, 0x03, 0x02, 0x01, 0x01]);
+//! ```
+//!
+//! Other labels are handled by [`serialize_der`] and [`deserialize_der`].
+
+use crate::{EncodeOptions, PemMessage};
+use ::serde::{de, ser, Deserialize, Deserializer, Serialize, Serializer};
+
+impl Serialize for PemMessage {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let text = match self.layout() {
+            Some(layout) => self.encode_with(layout.format()),
+            None => self.encode_with(&EncodeOptions::default()),
+        };
+        serializer.serialize_str(&text.map_err(ser::Error::custom)?)
+    }
+}
+
+impl<'de> Deserialize<'de> for PemMessage {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let text = String::deserialize(deserializer)?;
+        text.parse().map_err(de::Error::custom)
+    }
+}
+
+/// Serialize `der` as a PEM message labelled `label`
+pub fn serialize_der<S: Serializer>(
+    der: &[u8],
+    label: &str,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    let pem = PemMessage::new(label, der);
+    pem.serialize(serializer)
+}
+
+/// Deserialize the content of a PEM message, which must be labelled `label`
+pub fn deserialize_der<'de, D: Deserializer<'de>>(
+    label: &str,
+    deserializer: D,
+) -> Result<Vec<u8>, D::Error> {
+    let pem = PemMessage::deserialize(deserializer)?;
+    if pem.label != label {
+        return Err(de::Error::custom(format!(
+            "Expected label \"{}\", found \"{}\"",
+            label, pem.label
+        )));
+    }
+    Ok(pem.content)
+}
+
+macro_rules! der_module {
+    ($name:ident, $label:ident) => {
+        #[doc = concat!(
+                    "`with` module for DER fields written as PEM messages labelled [`",
+                    stringify!($label),
+                    "`](crate::",
+                    stringify!($label),
+                    ")"
+                )]
+        pub mod $name {
+            use ::serde::{Deserializer, Serializer};
+
+            pub fn serialize<S: Serializer>(der: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+                super::serialize_der(der, crate::$label, serializer)
+            }
+
+            pub fn deserialize<'de, D: Deserializer<'de>>(
+                deserializer: D,
+            ) -> Result<Vec<u8>, D::Error> {
+                super::deserialize_der(crate::$label, deserializer)
+            }
+        }
+    };
+}
+
+der_module!(certificate, CERTIFICATE_LABEL);
+der_module!(x509_crl, CRL_LABEL);
+der_module!(certificate_request, CERTREQ_LABEL);
+der_module!(pkcs7, PKCS7_LABEL);
+der_module!(cms, CMS_LABEL);
+der_module!(private_key, PRIVKEY_LABEL);
+der_module!(encrypted_private_key, ENC_PRIVKEY_LABEL);
+der_module!(attribute_certificate, ATTRCERT_LABEL);
+der_module!(public_key, PUBKEY_LABEL);
@@ -0,0 +1,85 @@
+use crate::error::Position;
+use std::ops::{Range, RangeInclusive};
+
+/// Location of a part of a parsed input
+///
+/// `end` is the position right after the last character of the part,
+/// which never includes the final line ending.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SourceSpan {
+    pub start: Position,
+    pub end: Position,
+}
+
+impl SourceSpan {
+    /// Span of `input[start..end]`, without its trailing whitespace
+    pub(crate) fn new(input: &str, start: usize, end: usize) -> Self {
+        let end = start + input[start..end].trim_end().len();
+        SourceSpan {
+            start: Position::from_offset(input, start),
+            end: Position::from_offset(input, end),
+        }
+    }
+
+    /// Byte offsets of the part in the input
+    pub fn byte_range(&self) -> Range<usize> {
+        self.start.offset..self.end.offset
+    }
+
+    /// 1-based numbers of the lines holding the part
+    pub fn lines(&self) -> RangeInclusive<usize> {
+        self.start.line..=self.end.line
+    }
+
+    /// Move a span found in an input starting at `base` onto the whole input
+    pub(crate) fn rebase(self, base: Position) -> Self {
+        SourceSpan {
+            start: self.start.rebase(base),
+            end: self.end.rebase(base),
+        }
+    }
+}
+
+/// Location of each part of a parsed PEM block
+///
+/// ```
+/// # use easypem::PemMessage;
+/// let text = "Subject: CN=Example
+/// -----BEGIN MESSAGE-----
+/// Comment: example
+///
+/// Zmlyc3Q=
+/// -----END MESSAGE-----";
+///
+/// let pem = text.parse::<PemMessage>().unwrap();
+/// let spans = pem.spans().unwrap();
+/// assert_eq!(spans.block.lines(), 2..=6);
+/// assert_eq!(&text[spans.content.unwrap().byte_range()], "Zmlyc3Q=");
+/// assert_eq!(pem.headers.fields[0].span.unwrap().lines(), 3..=3);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BlockSpans {
+    /// From the start of the pre-encapsulation boundary to the end of the
+    /// post-encapsulation boundary
+    pub block: SourceSpan,
+    /// `-----BEGIN` line
+    pub pre_eb: SourceSpan,
+    /// Header fields, if any
+    pub headers: Option<SourceSpan>,
+    /// Base64 text, if any
+    pub content: Option<SourceSpan>,
+    /// `-----END` line
+    pub post_eb: SourceSpan,
+}
+
+impl BlockSpans {
+    pub(crate) fn rebase(self, base: Position) -> Self {
+        BlockSpans {
+            block: self.block.rebase(base),
+            pre_eb: self.pre_eb.rebase(base),
+            headers: self.headers.map(|span| span.rebase(base)),
+            content: self.content.map(|span| span.rebase(base)),
+            post_eb: self.post_eb.rebase(base),
+        }
+    }
+}
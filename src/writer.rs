@@ -0,0 +1,224 @@
+use crate::error::PemResult;
+use crate::headers::PemHeader;
+use crate::parser::validate_label;
+use std::io::{self, Write};
+
+/// Number of input bytes encoded at once
+const CHUNK_BYTES: usize = 3 * 1024;
+
+/// Line ending written by [`PemWriter`] and
+/// [`PemMessage::encode_with`](crate::PemMessage::encode_with)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum LineEnding {
+    /// `\n`
+    #[default]
+    Lf,
+    /// `\r\n`
+    CrLf,
+    /// `\r`
+    Cr,
+}
+
+impl LineEnding {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            LineEnding::Lf => "\n",
+            LineEnding::CrLf => "\r\n",
+            LineEnding::Cr => "\r",
+        }
+    }
+}
+
+/// Options controlling how PEM text is written
+///
+/// The default options give the same output as `Display for PemMessage`.
+///
+/// ```
+/// # use easypem::{EncodeOptions, LineEnding, PemMessage};
+/// let pem = PemMessage::new("MESSAGE", &b"This is a message"[..]);
+///
+/// let options = EncodeOptions {
+///     line_width: 16,
+///     line_ending: LineEnding::CrLf,
+///     trailing_newline: true,
+///     ..Default::default()
+/// };
+/// assert_eq!(
+///     pem.encode_with(&options).unwrap(),
+///     "-----BEGIN MESSAGE-----\r\nVGhpcyBpcyBhIG1l\r\nc3NhZ2U=\r\n-----END MESSAGE-----\r\n"
+/// );
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EncodeOptions {
+    /// Number of base64 characters on each line of the content,
+    /// 0 to write it on a single line
+    pub line_width: usize,
+    pub line_ending: LineEnding,
+    /// Whether a line ending follows the post-encapsulation boundary
+    pub trailing_newline: bool,
+    /// Number of base64 characters on each continuation line of the
+    /// RFC 1421 header fields, 0 to write each value on a single line
+    ///
+    /// Header fields kept as found in the input are not refolded.
+    pub header_width: usize,
+}
+
+impl Default for EncodeOptions {
+    fn default() -> Self {
+        EncodeOptions {
+            line_width: 64,
+            line_ending: LineEnding::Lf,
+            trailing_newline: false,
+            header_width: 64,
+        }
+    }
+}
+
+/// Streaming PEM serializer over a [`Write`]
+///
+/// The encapsulation boundary and the headers are written on creation,
+/// then the content is base64 encoded and wrapped at 64 columns as it is
+/// written. [`finish`](PemWriter::finish) must be called to write the last
+/// line and the post-encapsulation boundary.
+///
+/// ```
+/// # use easypem::{PemWriter, headers::PemHeader};
+/// use std::io::Write;
+///
+/// let mut writer = PemWriter::new(Vec::new(), "MESSAGE", &PemHeader::default()).unwrap();
+/// writer.write_all(b"This is ").unwrap();
+/// writer.write_all(b"a message").unwrap();
+/// let output = writer.finish().unwrap();
+///
+/// assert_eq!(
+///     std::str::from_utf8(&output).unwrap(),
+///     "-----BEGIN MESSAGE-----\nVGhpcyBpcyBhIG1lc3NhZ2U=\n-----END MESSAGE-----\n"
+/// );
+/// ```
+#[derive(Debug)]
+pub struct PemWriter<W: Write> {
+    inner: W,
+    label: String,
+    options: EncodeOptions,
+    /// Input bytes not forming a whole base64 quantum yet
+    buf: [u8; 3],
+    buf_len: usize,
+    /// Number of base64 characters on the current line
+    column: usize,
+}
+
+impl<W: Write> PemWriter<W> {
+    /// Write the pre-encapsulation boundary and the headers to `inner`
+    ///
+    /// Unlike `Display for PemMessage`, a line ending is written after the
+    /// post-encapsulation boundary, so blocks can be written one after another.
+    pub fn new(inner: W, label: &str, headers: &PemHeader) -> PemResult<Self> {
+        let options = EncodeOptions {
+            trailing_newline: true,
+            ..Default::default()
+        };
+        Self::with_options(inner, label, headers, &options)
+    }
+
+    /// Write the pre-encapsulation boundary and the headers to `inner`,
+    /// formatted according to `options`
+    pub fn with_options(
+        mut inner: W,
+        label: &str,
+        headers: &PemHeader,
+        options: &EncodeOptions,
+    ) -> PemResult<Self> {
+        validate_label(label)?;
+        let eol = options.line_ending.as_str();
+        write!(inner, "-----BEGIN {}-----{}", label, eol)?;
+        if !headers.is_empty() {
+            let text = headers.to_string_folded(options.header_width);
+            write!(inner, "{}{}", text.replace('\n', eol), eol)?;
+        }
+        Ok(PemWriter {
+            inner,
+            label: label.to_owned(),
+            options: options.clone(),
+            buf: [0; 3],
+            buf_len: 0,
+            column: 0,
+        })
+    }
+
+    /// Write the remaining content and the post-encapsulation boundary,
+    /// returning the underlying writer
+    pub fn finish(mut self) -> io::Result<W> {
+        if self.buf_len > 0 {
+            let buf = self.buf;
+            self.write_base64(&buf[..self.buf_len])?;
+        }
+        let eol = self.options.line_ending.as_str();
+        if self.column > 0 {
+            self.inner.write_all(eol.as_bytes())?;
+        }
+        write!(self.inner, "-----END {}-----", &self.label)?;
+        if self.options.trailing_newline {
+            self.inner.write_all(eol.as_bytes())?;
+        }
+        self.inner.flush()?;
+        Ok(self.inner)
+    }
+
+    /// Encode `data` and write it, wrapping the lines at `line_width`
+    fn write_base64(&mut self, data: &[u8]) -> io::Result<()> {
+        let text = base64::encode(data);
+        let mut text = text.as_bytes();
+        let width = match self.options.line_width {
+            0 => usize::MAX,
+            width => width,
+        };
+        while !text.is_empty() {
+            let len = (width - self.column).min(text.len());
+            self.inner.write_all(&text[..len])?;
+            self.column += len;
+            text = &text[len..];
+            if self.column == width {
+                self.inner
+                    .write_all(self.options.line_ending.as_str().as_bytes())?;
+                self.column = 0;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<W: Write> Write for PemWriter<W> {
+    fn write(&mut self, mut data: &[u8]) -> io::Result<usize> {
+        let written = data.len();
+
+        if self.buf_len > 0 {
+            let fill = (3 - self.buf_len).min(data.len());
+            self.buf[self.buf_len..self.buf_len + fill].copy_from_slice(&data[..fill]);
+            self.buf_len += fill;
+            data = &data[fill..];
+            if self.buf_len < 3 {
+                return Ok(written);
+            }
+            let buf = self.buf;
+            self.write_base64(&buf)?;
+            self.buf_len = 0;
+        }
+
+        let whole = data.len() / 3 * 3;
+        for chunk in data[..whole].chunks(CHUNK_BYTES) {
+            self.write_base64(chunk)?;
+        }
+        let rest = &data[whole..];
+        self.buf[..rest.len()].copy_from_slice(rest);
+        self.buf_len = rest.len();
+        Ok(written)
+    }
+
+    /// Flush the underlying writer
+    ///
+    /// Bytes which do not fill a whole base64 quantum yet are kept until more
+    /// data is written or the writer is finished.
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
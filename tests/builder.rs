@@ -0,0 +1,169 @@
+extern crate easypem;
+
+use easypem::error::ErrorKind;
+use easypem::headers::{ContentDomain, ProcType, ProcTypeSpecifier};
+use easypem::*;
+
+#[test]
+fn build_roundtrip() {
+    let pem = PemBuilder::new()
+        .label(PRIVKEY_LABEL)
+        .content(&b"This is a message"[..])
+        .build()
+        .unwrap();
+    assert_eq!(pem.label, PRIVKEY_LABEL);
+    assert_eq!(pem.to_string().parse::<PemMessage>().unwrap(), pem);
+
+    let pem = PemBuilder::new()
+        .label("MESSAGE")
+        .proc_type(ProcType(4, ProcTypeSpecifier::MIC_CLEAR))
+        .content_domain(ContentDomain("RFC822".to_owned()))
+        .header("Comment", "first line\n  second line")
+        .content_from_reader(&b"This is a message"[..])
+        .unwrap()
+        .build()
+        .unwrap();
+    assert_eq!(
+        pem.to_string(),
+        "-----BEGIN MESSAGE-----
+Proc-Type: 4,MIC-CLEAR
+Content-Domain: RFC822
+Comment: first line
+  second line
+
+VGhpcyBpcyBhIG1lc3NhZ2U=
+-----END MESSAGE-----"
+    );
+    assert_eq!(pem.headers.fields.len(), 3);
+    assert_eq!(pem.to_string().parse::<PemMessage>().unwrap(), pem);
+}
+
+#[test]
+fn typed_header_fields() {
+    let pem = PemBuilder::new()
+        .label("RSA PRIVATE KEY")
+        .header("Proc-Type", "4,ENCRYPTED")
+        .header("DEK-Info", "DES-CBC,0011223344556677")
+        .build()
+        .unwrap();
+    assert_eq!(
+        pem.headers.proc_type,
+        Some(ProcType(4, ProcTypeSpecifier::ENCRYPTED))
+    );
+    assert_eq!(pem.headers.dek_info.unwrap().algorithm, "DES-CBC");
+}
+
+#[test]
+fn built_fields_have_no_span() {
+    let pem = PemBuilder::new()
+        .label("MESSAGE")
+        .header("Comment", "built")
+        .content(&b"This is a message"[..])
+        .build()
+        .unwrap();
+    assert!(pem.headers.fields.iter().all(|field| field.span.is_none()));
+
+    let parsed: PemMessage = pem.to_string().parse().unwrap();
+    assert!(parsed.headers.fields[0].span.is_some());
+    let pem = PemBuilder::new()
+        .label("MESSAGE")
+        .headers(parsed.headers)
+        .content(parsed.content)
+        .build()
+        .unwrap();
+    assert!(pem.headers.fields.iter().all(|field| field.span.is_none()));
+}
+
+#[test]
+fn build_errors() {
+    let err = PemBuilder::new()
+        .content(vec![1, 2, 3])
+        .build()
+        .unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidLabel);
+    for label in &["", "BAD--LABEL", " LEADING", "TRAILING-"] {
+        let err = PemBuilder::new().label(*label).build().unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidLabel, "{:?}", label);
+    }
+
+    let err = PemBuilder::new()
+        .label("MESSAGE")
+        .header("Bad Name", "value")
+        .build()
+        .unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidHeader);
+    let err = PemBuilder::new()
+        .label("MESSAGE")
+        .header("DEK-Info", "DES-CBC,not hex")
+        .build()
+        .unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidHeader);
+}
+
+#[test]
+fn typed_setters_are_checked() {
+    let err = PemBuilder::new()
+        .label("MESSAGE")
+        .content_domain(ContentDomain("RFC 822".to_owned()))
+        .content(b"This is a message".to_vec())
+        .build()
+        .unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidHeader);
+
+    let err = PemBuilder::new()
+        .label("MESSAGE")
+        .dek_info(headers::DEKInfo {
+            algorithm: "DES CBC".to_owned(),
+            parameter: vec![0; 8],
+        })
+        .content(b"This is a message".to_vec())
+        .build()
+        .unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidHeader);
+}
+
+#[test]
+fn duplicate_fields() {
+    let err = PemBuilder::new()
+        .label("MESSAGE")
+        .proc_type(ProcType(4, ProcTypeSpecifier::MIC_CLEAR))
+        .header("Proc-Type", "4,MIC-ONLY")
+        .content(b"This is a message".to_vec())
+        .build()
+        .unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidHeader);
+
+    let err = PemBuilder::new()
+        .label("MESSAGE")
+        .header("Content-Domain", "RFC822")
+        .header("Content-Domain", "RFC822")
+        .content(b"This is a message".to_vec())
+        .build()
+        .unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidHeader);
+
+    // Other fields may be repeated
+    let pem = PemBuilder::new()
+        .label("MESSAGE")
+        .header("Comment", "first")
+        .header("Comment", "second")
+        .content(b"This is a message".to_vec())
+        .build()
+        .unwrap();
+    assert_eq!(pem.to_string().parse::<PemMessage>().unwrap(), pem);
+}
+
+#[test]
+fn empty_content() {
+    let err = PemBuilder::new().label("MESSAGE").build().unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::MissingContent);
+
+    // RFC 1424 messages have headers and no content
+    let pem = PemBuilder::new()
+        .label("MESSAGE")
+        .header("Proc-Type", "4,CRL-RETRIEVAL-REQUEST")
+        .header("Issuer", "MAA=")
+        .build()
+        .unwrap();
+    assert_eq!(pem.to_string().parse::<PemMessage>().unwrap(), pem);
+}
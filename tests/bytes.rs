@@ -0,0 +1,136 @@
+extern crate easypem;
+
+mod common;
+
+use common::*;
+use easypem::error::ErrorKind;
+use easypem::*;
+use std::fs;
+
+#[test]
+fn same_as_str() {
+    for file in &["certificate.txt", "ec_privatekey_aes128.txt", "cms.txt"] {
+        let bytes = fs::read(locate_test_files(file)).unwrap();
+        let text = String::from_utf8(bytes.clone()).unwrap();
+        assert_eq!(
+            PemMessage::from_bytes(&bytes).unwrap(),
+            text.parse::<PemMessage>().unwrap()
+        );
+    }
+}
+
+#[test]
+fn non_ascii_explanatory_text() {
+    let bundle = b"Subject: CN=J\xC3\xBCrgen
+-----BEGIN FIRST-----
+Zmlyc3Q=
+-----END FIRST-----
+Garbage \xFF\xFE
+-----BEGIN SECOND-----
+c2Vjb25k
+-----END SECOND-----
+";
+    let pems = PemMessage::parse_all_bytes(bundle).unwrap();
+    assert_eq!(pems.len(), 2);
+    assert_eq!(pems[0].explanatory_text(), "Subject: CN=J\u{FC}rgen\n");
+    assert_eq!(pems[0].content, b"first");
+    assert_eq!(pems[1].explanatory_text(), "Garbage \u{FFFD}\u{FFFD}\n");
+    assert_eq!(pems[1].content, b"second");
+}
+
+#[test]
+fn non_ascii_in_block() {
+    let cases: &[(&[u8], usize, usize, usize)] = &[
+        // In the label
+        (
+            b"-----BEGIN M\xC9SSAGE-----\nZmlyc3Q=\n-----END MESSAGE-----",
+            12,
+            1,
+            13,
+        ),
+        // In a header
+        (
+            b"-----BEGIN MESSAGE-----\nComment: \xE9t\xE9\n\nZmlyc3Q=\n-----END MESSAGE-----",
+            33,
+            2,
+            10,
+        ),
+        // In the base64 text
+        (
+            b"-----BEGIN MESSAGE-----\nZmly\x80c3Q=\n-----END MESSAGE-----",
+            28,
+            2,
+            5,
+        ),
+        // After the base64 text, with no post-encapsulation boundary
+        (b"-----BEGIN MESSAGE-----\nZmlyc3Q=\n\xFF", 33, 3, 1),
+    ];
+    for &(input, offset, line, column) in cases {
+        let err = PemMessage::from_bytes(input).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::NonAscii, "{:?}", err);
+        let position = err.position().unwrap();
+        assert_eq!(
+            (position.offset, position.line, position.column),
+            (offset, line, column)
+        );
+    }
+}
+
+#[test]
+fn bundle_errors() {
+    let bundle = b"-----BEGIN FIRST-----
+Zmlyc3Q=
+-----END FIRST-----
+-----BEGIN BROKEN-----
+Zml\xFFyc3Q=
+-----END BROKEN-----
+-----BEGIN SECOND-----
+c2Vjb25k
+-----END SECOND-----
+";
+    let err = PemMessage::parse_all_bytes(bundle).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::NonAscii);
+    assert_eq!(err.position().unwrap().line, 5);
+
+    let err = PemMessage::from_bytes(b"no pem here \xFF\n").unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::MissingBeginBoundary);
+    assert!(PemMessage::parse_all_bytes(b"\xFF\n").unwrap().is_empty());
+}
+
+#[test]
+fn non_ascii_after_broken_block() {
+    let cases: &[(&[u8], ErrorKind, usize)] = &[
+        (
+            b"-----BEGIN A-----\nZm9v\n\n# Comment \xE9\n-----BEGIN B-----\nYmFy\n-----END B-----\n",
+            ErrorKind::MissingEndBoundary,
+            3,
+        ),
+        (
+            b"-----BEGIN A-----\nZm9v\n-----END B-----\n# Comment \xE9\n-----BEGIN B-----\nYmFy\n-----END B-----\n",
+            ErrorKind::LabelMismatch,
+            3,
+        ),
+        (
+            b"-----BEGIN A-----\nZm*v\n-----END A-----\n\xE9t\xE9\n-----BEGIN B-----\nYmFy\n-----END B-----\n",
+            ErrorKind::InvalidBase64,
+            2,
+        ),
+    ];
+    for &(input, kind, line) in cases {
+        let err = PemMessage::parse_all_bytes(input).unwrap_err();
+        assert_eq!(err.kind(), kind, "{:?}", err);
+        assert_eq!(err.position().unwrap().line, line);
+    }
+}
+
+#[test]
+fn failure_before_block() {
+    let cases: &[&[u8]] = &[
+        b"x-----BEGIN A-----\r-----BEGIN A-----",
+        b"\n-----BEGIN A-----\xC3\xA9",
+    ];
+    for input in cases {
+        assert!(PemMessage::from_bytes(input).is_err());
+        assert!(PemMessage::parse_all_bytes(input).is_err());
+    }
+}
@@ -0,0 +1,144 @@
+#![cfg(feature = "cli")]
+extern crate easypem;
+
+mod common;
+
+use common::*;
+use easypem::*;
+use std::fs;
+use std::path::PathBuf;
+use std::process::{Command, Output};
+
+fn temp_dir(name: &str) -> PathBuf {
+    let mut dir = PathBuf::from(env!("CARGO_TARGET_TMPDIR"));
+    dir.push(name);
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+fn easypem(args: &[&str]) -> Output {
+    let output = Command::new(env!("CARGO_BIN_EXE_easypem"))
+        .args(args)
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    output
+}
+
+#[test]
+fn split_and_join() {
+    let dir = temp_dir("split_and_join");
+    let bundle = dir.join("bundle.pem");
+    let text: String = read_bundle(&["certificate.txt", "crl.txt", "publickey.txt"], "\n") + "\n";
+    fs::write(&bundle, text).unwrap();
+    let prefix = dir.join("part-");
+    let output = easypem(&[
+        "split",
+        bundle.to_str().unwrap(),
+        "--prefix",
+        prefix.to_str().unwrap(),
+    ]);
+
+    let files: Vec<&str> = std::str::from_utf8(&output.stdout)
+        .unwrap()
+        .lines()
+        .collect();
+    let original = PemMessage::parse_all(&fs::read_to_string(&bundle).unwrap()).unwrap();
+    assert_eq!(files.len(), original.len());
+    for (file, pem) in files.iter().zip(&original) {
+        let part: PemMessage = fs::read_to_string(file).unwrap().parse().unwrap();
+        assert_eq!(
+            (part.label, part.content),
+            (pem.label.clone(), pem.content.clone())
+        );
+    }
+
+    let joined = dir.join("joined.pem");
+    let mut args = vec!["join"];
+    args.extend(&files);
+    args.extend(&["-o", joined.to_str().unwrap()]);
+    easypem(&args);
+    let joined = PemMessage::parse_all(&fs::read_to_string(&joined).unwrap()).unwrap();
+    assert_eq!(joined.len(), original.len());
+    for (pem, original) in joined.iter().zip(&original) {
+        assert_eq!(pem.label, original.label);
+        assert_eq!(pem.content, original.content);
+    }
+}
+
+#[test]
+fn der_roundtrip() {
+    let dir = temp_dir("der_roundtrip");
+    let certificate = locate_test_files("certificate.txt");
+    let der = dir.join("certificate.der");
+    easypem(&[
+        "to-der",
+        certificate.to_str().unwrap(),
+        "-o",
+        der.to_str().unwrap(),
+    ]);
+
+    let pem: PemMessage = fs::read_to_string(&certificate).unwrap().parse().unwrap();
+    assert_eq!(fs::read(&der).unwrap(), pem.content);
+
+    let output = easypem(&["from-der", der.to_str().unwrap()]);
+    let converted: PemMessage = std::str::from_utf8(&output.stdout)
+        .unwrap()
+        .parse()
+        .unwrap();
+    assert_eq!(converted.label, CERTIFICATE_LABEL);
+    assert_eq!(converted.content, pem.content);
+}
+
+#[test]
+fn inspect_and_relabel() {
+    let pem = PemMessage::new("MESSAGE", &b"abc"[..]);
+    let dir = temp_dir("inspect_and_relabel");
+    let input = dir.join("message.pem");
+    fs::write(&input, format!("{}\n{}\n", pem, pem)).unwrap();
+
+    let output = easypem(&["inspect", input.to_str().unwrap()]);
+    let text = String::from_utf8(output.stdout).unwrap();
+    assert!(text.contains("Label: MESSAGE\n"));
+    assert!(text.contains("Length: 3 bytes\n"));
+    assert!(text.contains(
+        "SHA-256: BA:78:16:BF:8F:01:CF:EA:41:41:40:DE:5D:AE:22:23:\
+         B0:03:61:A3:96:17:7A:9C:B4:10:FF:61:F2:00:15:AD\n"
+    ));
+
+    let output = easypem(&["relabel", "DATA", input.to_str().unwrap(), "--block", "2"]);
+    let blocks = PemMessage::parse_all(std::str::from_utf8(&output.stdout).unwrap()).unwrap();
+    assert_eq!(blocks[0].label, "MESSAGE");
+    assert_eq!(blocks[1].label, "DATA");
+    assert_eq!(blocks[1].content, pem.content);
+
+    let output = Command::new(env!("CARGO_BIN_EXE_easypem"))
+        .args(["relabel", "BAD--LABEL", input.to_str().unwrap()])
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+}
+
+#[test]
+fn options_of_other_commands() {
+    let cases: &[&[&str]] = &[
+        &["inspect", "-o", "out.pem"],
+        &["split", "--block", "2"],
+        &["join", "a.pem", "--label", "X"],
+        &["to-der", "--prefix", "x-"],
+    ];
+    for args in cases {
+        let output = Command::new(env!("CARGO_BIN_EXE_easypem"))
+            .args(*args)
+            .output()
+            .unwrap();
+        assert!(!output.status.success(), "{:?}", args);
+        let stderr = String::from_utf8(output.stderr).unwrap();
+        assert!(stderr.contains("Unknown option"), "{}", stderr);
+    }
+}
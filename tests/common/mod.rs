@@ -0,0 +1,35 @@
+//! Helpers shared by the integration tests
+//!
+//! Each test file only uses some of them.
+#![allow(dead_code)]
+
+use easypem::PemMessage;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Path of a file of `tests/assets`
+pub fn locate_test_files<P: AsRef<Path>>(path: P) -> PathBuf {
+    let mut abspath = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    abspath.push("tests/assets/");
+    abspath.push(path);
+    abspath
+}
+
+/// Text of a file of `tests/assets`
+pub fn read_asset<P: AsRef<Path>>(path: P) -> String {
+    fs::read_to_string(locate_test_files(path)).unwrap()
+}
+
+/// Message of a file of `tests/assets`
+pub fn read_pem<P: AsRef<Path>>(path: P) -> PemMessage {
+    read_asset(path).parse().unwrap()
+}
+
+/// Text of files of `tests/assets`, joined by `separator`
+pub fn read_bundle(files: &[&str], separator: &str) -> String {
+    files
+        .iter()
+        .map(read_asset)
+        .collect::<Vec<_>>()
+        .join(separator)
+}
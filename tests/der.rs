@@ -0,0 +1,62 @@
+extern crate easypem;
+
+mod common;
+
+use common::*;
+use easypem::error::ErrorKind;
+use easypem::*;
+
+#[test]
+fn infer_labels() {
+    let cases = [
+        ("attr_cert.txt", Some(PemLabel::AttributeCertificate)),
+        ("cert_req.txt", Some(PemLabel::CertificateRequest)),
+        ("certificate.txt", Some(PemLabel::Certificate)),
+        ("cms.txt", Some(PemLabel::Cms)),
+        ("pkcs7.txt", Some(PemLabel::Cms)),
+        ("crl.txt", Some(PemLabel::X509Crl)),
+        ("ec_pkcs8.txt", Some(PemLabel::PrivateKey)),
+        ("privatekey.txt", Some(PemLabel::PrivateKey)),
+        ("enc_privatekey.txt", Some(PemLabel::EncryptedPrivateKey)),
+        (
+            "ec_pkcs8_aes256_sha256.txt",
+            Some(PemLabel::EncryptedPrivateKey),
+        ),
+        ("publickey.txt", Some(PemLabel::PublicKey)),
+        // SEC 1 ECPrivateKey has no RFC 7468 label
+        ("ec_privatekey.txt", None),
+    ];
+    for (file, label) in &cases {
+        let pem = read_pem(file);
+        assert_eq!(&PemLabel::infer(&pem.content), label, "{}", file);
+    }
+
+    assert_eq!(PemLabel::infer(&[]), None);
+    assert_eq!(PemLabel::infer(&[0x30, 0x00]), None);
+    // Trailing data after the outer element
+    let mut der = read_pem("publickey.txt").content;
+    der.push(0);
+    assert_eq!(PemLabel::infer(&der), None);
+}
+
+#[test]
+fn der_roundtrip() {
+    let pem = read_pem("certificate.txt");
+    let der = pem.to_der().unwrap();
+    let rebuilt = PemMessage::from_der(CERTIFICATE_LABEL, der).unwrap();
+    assert_eq!(rebuilt.to_string(), pem.to_string());
+}
+
+#[test]
+fn der_errors() {
+    let err = PemMessage::from_der(CERTIFICATE_LABEL, b"not DER".to_vec()).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidDer);
+    let err = PemMessage::from_der("BAD--LABEL", vec![0x30, 0x00]).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidLabel);
+
+    let encrypted = read_pem("ec_privatekey_aes128.txt");
+    assert_eq!(
+        encrypted.to_der().unwrap_err().kind(),
+        ErrorKind::InvalidDer
+    );
+}
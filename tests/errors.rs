@@ -0,0 +1,158 @@
+extern crate easypem;
+
+use easypem::error::{ErrorKind, Position};
+use easypem::*;
+use std::error::Error as _;
+
+fn parse_error(text: &str) -> (ErrorKind, Position) {
+    let err = text.parse::<PemMessage>().unwrap_err();
+    (err.kind(), err.position().unwrap())
+}
+
+#[test]
+fn error_kinds() {
+    let cases: &[(&str, ErrorKind, usize, usize)] = &[
+        ("no block here\n", ErrorKind::MissingBeginBoundary, 2, 1),
+        (
+            "-----BEGIN BAD--LABEL-----\nZm9v\n-----END BAD--LABEL-----",
+            ErrorKind::InvalidLabel,
+            1,
+            12,
+        ),
+        (
+            "-----BEGIN MESSAGE-----\nZm9v\n-----END OTHER-----",
+            ErrorKind::LabelMismatch,
+            3,
+            10,
+        ),
+        (
+            "-----BEGIN MESSAGE-----\nZm9v\nYmFy\n",
+            ErrorKind::MissingEndBoundary,
+            4,
+            1,
+        ),
+        (
+            "-----BEGIN MESSAGE-----\nZm9v\nYm*y\n-----END MESSAGE-----",
+            ErrorKind::InvalidBase64,
+            3,
+            3,
+        ),
+        (
+            "-----BEGIN MESSAGE-----\n-----END MESSAGE-----",
+            ErrorKind::MissingContent,
+            2,
+            1,
+        ),
+        (
+            "-----BEGIN MESSAGE-----\nComment: x\nBad header\n\nZm9v\n-----END MESSAGE-----",
+            ErrorKind::InvalidHeader,
+            3,
+            1,
+        ),
+        (
+            "-----BEGIN MESSAGE-----\nProc-Type: 4,SECRET\n\nZm9v\n-----END MESSAGE-----",
+            ErrorKind::UnsupportedProcType,
+            2,
+            14,
+        ),
+    ];
+    for &(text, kind, line, column) in cases {
+        // Each line ending accepted by the grammar counts as one line
+        for eol in &["\n", "\r\n", "\r"] {
+            let text = text.replace('\n', eol);
+            let (found, position) = parse_error(&text);
+            assert_eq!(found, kind, "{:?}", text);
+            assert_eq!(
+                (position.line, position.column),
+                (line, column),
+                "{:?}",
+                text
+            );
+        }
+    }
+}
+
+#[test]
+fn error_offsets() {
+    let text = "Explanatory text\n-----BEGIN MESSAGE-----\nZm9v\nYm*y\n-----END MESSAGE-----";
+    let err = text.parse::<PemMessage>().unwrap_err();
+    let position = err.position().unwrap();
+    assert_eq!(position.offset, text.find('*').unwrap());
+    assert!(err.to_string().starts_with("line 4, column 3: "), "{}", err);
+
+    // Base64 errors found after the grammar are located too
+    let text = "-----BEGIN MESSAGE-----\nZm9v\nZ===\n-----END MESSAGE-----";
+    let err = PemMessage::parse_with(
+        text,
+        &ParseOptions {
+            strictness: Strictness::Lax,
+            ..Default::default()
+        },
+    )
+    .unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidBase64);
+    assert_eq!(err.position().unwrap().line, 3);
+}
+
+#[test]
+fn error_source() {
+    let err = "-----BEGIN MESSAGE-----\nZm9v\n-----END OTHER-----"
+        .parse::<PemMessage>()
+        .unwrap_err();
+    assert!(err.source().is_some());
+
+    let err = PemWriter::new(Vec::new(), "BAD--LABEL", &Default::default()).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidLabel);
+    assert!(err.position().is_none());
+}
+
+#[test]
+fn reader_error_positions() {
+    let text = "-----BEGIN MESSAGE-----\r\nComment: x\r\nBad header\r\n\r\nZm9v\r\n-----END MESSAGE-----\r\n";
+    let err = PemReader::new(text.as_bytes()).next().unwrap().unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidHeader);
+    let position = err.position().unwrap();
+    assert_eq!(position.line, 3);
+    assert_eq!(
+        &text[position.offset - position.column + 1..][..10],
+        "Bad header"
+    );
+
+    let text = "-----BEGIN MESSAGE-----\nZm9v\nYm*y\n-----END MESSAGE-----\n";
+    let err = PemReader::new(text.as_bytes()).next().unwrap().unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidBase64);
+    assert_eq!(err.position().unwrap().offset, text.find('*').unwrap());
+}
+
+#[test]
+fn canonical_base64() {
+    let options = ParseOptions {
+        canonical_base64: true,
+        ..Default::default()
+    };
+    let full_line = "A".repeat(64);
+    let cases: &[(String, usize)] = &[
+        (format!("{}\nZh==", full_line), 3),
+        (format!("{}\nZg", full_line), 3),
+        ("Zm9v=".to_owned(), 2),
+        ("Zm9v\nYmFy".to_owned(), 2),
+    ];
+    for (content, line) in cases {
+        let text = format!(
+            "-----BEGIN MESSAGE-----\n{}\n-----END MESSAGE-----",
+            content
+        );
+        let err = PemMessage::parse_with(&text, &options).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidBase64, "{}", err);
+        assert_eq!(err.position().unwrap().line, *line, "{}", err);
+    }
+
+    let text = format!(
+        "-----BEGIN MESSAGE-----\n{}\nZg==\n-----END MESSAGE-----",
+        full_line
+    );
+    assert_eq!(
+        PemMessage::parse_with(&text, &options).unwrap(),
+        text.parse::<PemMessage>().unwrap()
+    );
+}
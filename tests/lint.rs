@@ -0,0 +1,156 @@
+extern crate easypem;
+
+mod common;
+
+use common::*;
+use easypem::lint::{lint, DiagnosticKind, Severity};
+
+/// Kind, line and column of a diagnostic
+type Found = (DiagnosticKind, usize, usize);
+
+fn lint_kinds(text: &str) -> Vec<Found> {
+    lint(text)
+        .into_iter()
+        .map(|d| (d.kind, d.position.line, d.position.column))
+        .collect()
+}
+
+#[test]
+fn clean_assets() {
+    for file in &[
+        "certificate.txt",
+        "crl.txt",
+        "publickey.txt",
+        "privatekey.txt",
+    ] {
+        let text = read_asset(file);
+        assert_eq!(lint(&text), [], "{}", file);
+    }
+}
+
+#[test]
+fn diagnostics() {
+    let cases: &[(&str, &[Found])] = &[
+        (
+            "-----BEGIN MESSAGE-----\nZm9v\n-----END OTHER-----",
+            &[
+                (DiagnosticKind::NonStandardLabel, 1, 12),
+                (DiagnosticKind::LabelMismatch, 3, 10),
+            ],
+        ),
+        (
+            "-----BEGIN X509 CERTIFICATE-----\nZm9v\n-----END X509 CERTIFICATE-----",
+            &[(DiagnosticKind::NonStandardLabel, 1, 12)],
+        ),
+        (
+            "-----BEGIN CERTIFICATE-----\nZm9vYmFy \nZm9v\n-----END CERTIFICATE-----",
+            &[
+                (DiagnosticKind::TrailingWhitespace, 2, 9),
+                (DiagnosticKind::ShortLine, 2, 9),
+            ],
+        ),
+        (
+            &format!(
+                "-----BEGIN CERTIFICATE-----\n{}\n-----END CERTIFICATE-----",
+                "A".repeat(68)
+            ),
+            &[(DiagnosticKind::LongLine, 2, 65)],
+        ),
+        (
+            "-----BEGIN CERTIFICATE-----\nZh\n-----END CERTIFICATE-----",
+            &[
+                (DiagnosticKind::NonCanonicalBits, 2, 2),
+                (DiagnosticKind::MissingPadding, 2, 3),
+            ],
+        ),
+        (
+            "-----BEGIN CERTIFICATE-----\nZg===\n-----END CERTIFICATE-----",
+            &[(DiagnosticKind::ExcessPadding, 2, 5)],
+        ),
+        (
+            "-----BEGIN CERTIFICATE-----\nComment: x\n\nZm9v\n-----END CERTIFICATE-----",
+            &[(DiagnosticKind::HeadersInRfc7468Block, 2, 1)],
+        ),
+        (
+            "Subject: x\n\n-----BEGIN CERTIFICATE-----\r\nZm9v\n-----END CERTIFICATE-----\n",
+            &[
+                (DiagnosticKind::ExplanatoryText, 1, 1),
+                (DiagnosticKind::MixedLineEndings, 3, 28),
+            ],
+        ),
+        (
+            "-----BEGIN CERTIFICATE-----\nZm*v\n-----BEGIN CERTIFICATE-----\n\n-----END CERTIFICATE-----",
+            &[
+                (DiagnosticKind::MissingEndBoundary, 1, 1),
+                (DiagnosticKind::MissingContent, 5, 1),
+            ],
+        ),
+        (
+            "  -----BEGIN CERTIFICATE-----\nZm9v\n\t-----END CERTIFICATE-----",
+            &[
+                (DiagnosticKind::IndentedBoundary, 1, 1),
+                (DiagnosticKind::IndentedBoundary, 3, 1),
+            ],
+        ),
+        ("no block here", &[
+            (DiagnosticKind::ExplanatoryText, 1, 1),
+            (DiagnosticKind::MissingBlock, 1, 14),
+        ]),
+    ];
+    for &(text, expected) in cases {
+        assert_eq!(lint_kinds(text), expected, "{:?}", text);
+    }
+}
+
+#[test]
+fn cr_line_endings() {
+    let text = "Text\r-----BEGIN CERTIFICATE-----\rZm9vYmFy \rZm9v\r-----END CRL-----\r";
+    assert_eq!(
+        lint_kinds(text),
+        [
+            (DiagnosticKind::ExplanatoryText, 1, 1),
+            (DiagnosticKind::TrailingWhitespace, 3, 9),
+            (DiagnosticKind::ShortLine, 3, 9),
+            (DiagnosticKind::LabelMismatch, 5, 10),
+        ]
+    );
+}
+
+#[test]
+fn preferred_labels() {
+    let text = "-----BEGIN X.509 CERTIFICATE-----\nZm9v\n-----END X.509 CERTIFICATE-----\n\
+                -----BEGIN CRL-----\nZm9v\n-----END CRL-----";
+    let messages = lint(text)
+        .into_iter()
+        .map(|d| d.message)
+        .collect::<Vec<_>>();
+    assert_eq!(
+        messages,
+        [
+            "Label \"X.509 CERTIFICATE\" is not defined by RFC 7468, use \"CERTIFICATE\"",
+            "Label \"CRL\" is not defined by RFC 7468, use \"X509 CRL\"",
+        ]
+    );
+}
+
+#[test]
+fn severities() {
+    let text = "Text\n-----BEGIN CERTIFICATE-----\nZh\n-----END CRL-----";
+    let diagnostics = lint(text);
+    let severities = diagnostics.iter().map(|d| d.severity).collect::<Vec<_>>();
+    assert_eq!(
+        severities,
+        [
+            Severity::Info,
+            Severity::Warning,
+            Severity::Warning,
+            Severity::Error
+        ]
+    );
+    assert!(diagnostics.iter().any(|d| d.severity >= Severity::Warning));
+    assert_eq!(
+        diagnostics[3].to_string(),
+        "line 4, column 10: error: \
+         Post-encapsulation boundary does not match label \"CERTIFICATE\""
+    );
+}
@@ -0,0 +1,136 @@
+extern crate easypem;
+
+mod common;
+
+use common::*;
+use easypem::*;
+
+#[test]
+fn parse_all_bundle() {
+    let bundle = read_bundle(
+        &["certificate.txt", "privatekey.txt", "publickey.txt"],
+        "\n",
+    );
+    let pems = PemMessage::parse_all(&bundle).unwrap();
+
+    let labels = pems.iter().map(|p| p.label.as_str()).collect::<Vec<_>>();
+    assert_eq!(labels, [CERTIFICATE_LABEL, PRIVKEY_LABEL, PUBKEY_LABEL]);
+    for (pem, file) in pems
+        .iter()
+        .zip(&["certificate.txt", "privatekey.txt", "publickey.txt"])
+    {
+        let single = read_pem(file);
+        assert_eq!(pem.headers, single.headers);
+        assert_eq!(pem.content, single.content);
+    }
+    // The blank lines between the blocks
+    assert_eq!(pems[0].explanatory_text(), "");
+    assert_eq!(pems[1].explanatory_text(), "\n");
+    assert_eq!(pems[2].explanatory_text(), "\n");
+}
+
+#[test]
+fn parse_all_empty() {
+    assert!(PemMessage::parse_all("").unwrap().is_empty());
+    assert!(PemMessage::parse_all("no pem here\n").unwrap().is_empty());
+}
+
+#[test]
+fn error_for_each_block() {
+    let bundle = "-----BEGIN FIRST-----
+Zmlyc3Q=
+-----END FIRST-----
+-----BEGIN BROKEN-----
+Zmlyc3Q=
+-----END MISMATCH-----
+-----BEGIN SECOND-----
+c2Vjb25k
+-----END SECOND-----
+";
+    let results = PemBlocks::new(bundle).collect::<Vec<_>>();
+    assert_eq!(results.len(), 3);
+    assert_eq!(results[0].as_ref().unwrap().content, b"first");
+    let err = results[1].as_ref().unwrap_err();
+    assert_eq!(err.kind(), error::ErrorKind::LabelMismatch);
+    assert_eq!(err.position().unwrap().line, 6);
+    assert_eq!(results[2].as_ref().unwrap().content, b"second");
+
+    assert!(PemMessage::parse_all(bundle).is_err());
+}
+
+#[test]
+fn block_spans() {
+    let bundle = "Explanatory text
+-----BEGIN FIRST-----
+Zmlyc3Q=
+-----END FIRST-----
+
+-----BEGIN SECOND-----
+Proc-Type: 4,CRL
+CRL: Y3Js
+Originator-Certificate:
+ Y2VydA==
+
+-----END SECOND-----
+";
+    let pems = PemMessage::parse_all(bundle).unwrap();
+
+    let spans = pems[0].spans().unwrap();
+    assert_eq!(spans.block.lines(), 2..=4);
+    assert_eq!(&bundle[spans.pre_eb.byte_range()], "-----BEGIN FIRST-----");
+    assert_eq!(&bundle[spans.content.unwrap().byte_range()], "Zmlyc3Q=");
+    assert_eq!(spans.post_eb.start.line, 4);
+    assert!(spans.headers.is_none());
+
+    let spans = pems[1].spans().unwrap();
+    assert_eq!(spans.block.lines(), 6..=12);
+    assert_eq!(spans.headers.unwrap().lines(), 7..=10);
+    assert!(spans.content.is_none());
+    let fields = &pems[1].headers.fields;
+    let lines = fields
+        .iter()
+        .map(|field| field.span.unwrap().lines())
+        .collect::<Vec<_>>();
+    assert_eq!(lines, [7..=7, 8..=8, 9..=10]);
+    let cert = fields[2].span.unwrap();
+    assert_eq!(
+        &bundle[cert.byte_range()],
+        "Originator-Certificate:\n Y2VydA=="
+    );
+    assert_eq!((cert.end.line, cert.end.column), (10, 10));
+
+    // The same spans are found from bytes, and with strict parsing
+    let from_bytes = PemMessage::parse_all_bytes(bundle.as_bytes()).unwrap();
+    assert_eq!(from_bytes[1].spans(), pems[1].spans());
+    let options = ParseOptions {
+        strictness: Strictness::Strict,
+        ..Default::default()
+    };
+    let strict = PemBlocks::with_options(bundle, options).next().unwrap();
+    assert_eq!(strict.unwrap().spans(), pems[0].spans());
+}
+
+#[test]
+fn indented_begin_is_an_error() {
+    let broken = "  -----BEGIN BROKEN-----\nZm9v\n-----END BROKEN-----\n";
+    let block = "-----BEGIN SECOND-----\nc2Vjb25k\n-----END SECOND-----\n";
+
+    // Before another block
+    let bundle = format!("{}{}", broken, block);
+    let results = PemBlocks::new(&bundle).collect::<Vec<_>>();
+    assert_eq!(results.len(), 2);
+    let err = results[0].as_ref().unwrap_err();
+    assert_eq!(err.kind(), error::ErrorKind::Syntax);
+    assert_eq!(err.position().unwrap().line, 1);
+    assert_eq!(results[1].as_ref().unwrap().content, b"second");
+    assert_eq!(results[1].as_ref().unwrap().explanatory_text(), "");
+
+    // As the last block
+    let bundle = format!("{}{}", block, broken);
+    let results = PemBlocks::new(&bundle).collect::<Vec<_>>();
+    assert_eq!(results.len(), 2);
+    assert_eq!(results[0].as_ref().unwrap().content, b"second");
+    let err = results[1].as_ref().unwrap_err();
+    assert_eq!(err.kind(), error::ErrorKind::Syntax);
+    assert_eq!(err.position().unwrap().line, 4);
+}
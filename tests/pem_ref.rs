@@ -0,0 +1,72 @@
+extern crate easypem;
+
+mod common;
+
+use common::*;
+use easypem::error::ErrorKind;
+use easypem::*;
+
+#[test]
+fn same_as_owned() {
+    let mut bundle: String =
+        read_bundle(&["certificate.txt", "crl.txt", "cert_req.txt"], "\n") + "\n";
+    bundle.push_str(
+        "Text\n-----BEGIN MESSAGE-----\nComment: a\n  b\n\nZm9v\n-----END MESSAGE-----\n",
+    );
+    let owned = PemMessage::parse_all(&bundle).unwrap();
+    let borrowed = PemRef::parse_all(&bundle).unwrap();
+    assert_eq!(borrowed.len(), owned.len());
+
+    for (pem_ref, pem) in borrowed.iter().zip(&owned) {
+        assert_eq!(pem_ref.label(), pem.label);
+        assert_eq!(pem_ref.explanatory_text(), pem.explanatory_text());
+        assert_eq!(pem_ref.decoded_len(), pem.content.len());
+        let mut content = b"prefix".to_vec();
+        pem_ref.decode_into(&mut content).unwrap();
+        assert_eq!(&content[6..], &pem.content[..]);
+        let message = pem_ref.to_message().unwrap();
+        assert_eq!(&message, pem);
+        let spans = |pem: &PemMessage| {
+            let fields = pem.headers.fields.iter();
+            fields.map(|field| field.span).collect::<Vec<_>>()
+        };
+        assert_eq!(spans(&message), spans(pem));
+    }
+}
+
+#[test]
+fn raw_parts() {
+    let text = "-----BEGIN MESSAGE-----\nComment: first\n\nZm9v\nYmFy\n-----END MESSAGE-----";
+    let pem = PemRef::parse(text).unwrap();
+    assert_eq!(pem.headers(), "Comment: first\n");
+    assert_eq!(pem.base64(), "Zm9v\nYmFy");
+
+    let pem = PemRef::parse("-----BEGIN MESSAGE-----\nZm9v\n-----END MESSAGE-----").unwrap();
+    assert_eq!(pem.headers(), "");
+}
+
+#[test]
+fn errors() {
+    let text = "-----BEGIN MESSAGE-----\nZm9v\n-----END OTHER-----";
+    assert_eq!(
+        PemRef::parse(text).unwrap_err().kind(),
+        ErrorKind::LabelMismatch
+    );
+
+    // Headers are only interpreted when converting
+    let text = "First\n-----BEGIN MESSAGE-----\nProc-Type: 4,SECRET\n\nZm9v\n-----END MESSAGE-----";
+    let pem = PemRef::parse(text).unwrap();
+    let err = pem.to_message().unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::UnsupportedProcType);
+    assert_eq!(err.position().unwrap().line, 3);
+
+    let options = ParseOptions {
+        canonical_base64: true,
+        ..Default::default()
+    };
+    let text = "-----BEGIN A-----\nZm9v\n-----END A-----\n-----BEGIN B-----\nZh==\n-----END B-----";
+    assert!(PemRef::parse_with(text, &options).is_ok());
+    let err = PemRef::parse_all_with(text, &options).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidBase64);
+    assert_eq!(err.position().unwrap().line, 5);
+}
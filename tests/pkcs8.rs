@@ -0,0 +1,181 @@
+#![cfg(feature = "pkcs8")]
+
+extern crate easypem;
+
+mod common;
+
+use common::*;
+use easypem::error::ErrorKind;
+use easypem::pkcs8::{Cipher, DecryptLimits, EncryptOptions, Kdf, Prf};
+use easypem::*;
+
+const PASSPHRASE: &[u8] = b"easypem";
+
+#[test]
+fn decrypt_openssl_keys() {
+    let plain = read_pem("ec_pkcs8.txt");
+    for name in &[
+        "ec_pkcs8_aes128_sha1.txt",
+        "ec_pkcs8_aes256_sha256.txt",
+        "ec_pkcs8_des3.txt",
+        "ec_pkcs8_scrypt_aes192.txt",
+    ] {
+        let encrypted = read_pem(name);
+        assert_eq!(&encrypted.label, ENC_PRIVKEY_LABEL);
+        let decrypted = encrypted.decrypt_pkcs8(PASSPHRASE).unwrap();
+        assert_eq!(decrypted, plain, "{}", name);
+    }
+}
+
+#[test]
+fn decrypt_wrong_passphrase() {
+    let encrypted = read_pem("ec_pkcs8_aes256_sha256.txt");
+    assert!(encrypted.decrypt_pkcs8(b"wrong").is_err());
+}
+
+#[test]
+fn decrypt_not_encrypted() {
+    let plain = read_pem("ec_pkcs8.txt");
+    assert!(plain.decrypt_pkcs8(PASSPHRASE).is_err());
+
+    let mut truncated = read_pem("ec_pkcs8_aes256_sha256.txt");
+    truncated.content.truncate(20);
+    assert!(truncated.decrypt_pkcs8(PASSPHRASE).is_err());
+}
+
+/// DER element with a short length
+fn tlv(tag: u8, content: &[u8]) -> Vec<u8> {
+    let mut out = vec![tag, content.len() as u8];
+    out.extend_from_slice(content);
+    out
+}
+
+/// `ENCRYPTED PRIVATE KEY` message with the given KDF and AES-128-CBC
+fn encrypted_key(kdf_oid: &[u8], kdf_params: &[u8]) -> PemMessage {
+    let salt = tlv(0x04, &[0; 16]);
+    let kdf = [
+        tlv(0x06, kdf_oid),
+        tlv(0x30, &[salt, kdf_params.to_vec()].concat()),
+    ]
+    .concat();
+    let aes128_cbc = [
+        tlv(0x06, &hex::decode("608648016503040102").unwrap()),
+        tlv(0x04, &[0; 16]),
+    ]
+    .concat();
+    let pbes2 = [
+        tlv(0x06, &hex::decode("2a864886f70d01050d").unwrap()),
+        tlv(0x30, &[tlv(0x30, &kdf), tlv(0x30, &aes128_cbc)].concat()),
+    ]
+    .concat();
+    let info = [tlv(0x30, &pbes2), tlv(0x04, &[0; 16])].concat();
+    PemMessage::new(ENC_PRIVKEY_LABEL, tlv(0x30, &info))
+}
+
+#[test]
+fn decrypt_kdf_limits() {
+    // PBKDF2 with 2^31 - 1 iterations
+    let pbkdf2_oid = hex::decode("2a864886f70d01050c").unwrap();
+    let key = encrypted_key(&pbkdf2_oid, &tlv(0x02, &[0x7f, 0xff, 0xff, 0xff]));
+    let err = key.decrypt_pkcs8(PASSPHRASE).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::LimitExceeded);
+
+    // scrypt with N = 2^40 and r = 8
+    let scrypt_oid = hex::decode("2b06010401da47040b").unwrap();
+    let params = [
+        tlv(0x02, &[0x01, 0, 0, 0, 0, 0]),
+        tlv(0x02, &[8]),
+        tlv(0x02, &[1]),
+    ]
+    .concat();
+    let err = encrypted_key(&scrypt_oid, &params)
+        .decrypt_pkcs8(PASSPHRASE)
+        .unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::LimitExceeded);
+
+    // The limits can be lowered or raised
+    let encrypted = read_pem("ec_pkcs8_aes256_sha256.txt");
+    let limits = DecryptLimits {
+        max_pbkdf2_iterations: 1000,
+        ..Default::default()
+    };
+    let err = encrypted
+        .decrypt_pkcs8_with(PASSPHRASE, &limits)
+        .unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::LimitExceeded);
+    let encrypted = read_pem("ec_pkcs8_scrypt_aes192.txt");
+    let limits = DecryptLimits {
+        max_scrypt_memory: 16 * 1024 * 1024,
+        ..Default::default()
+    };
+    let err = encrypted
+        .decrypt_pkcs8_with(PASSPHRASE, &limits)
+        .unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::LimitExceeded);
+    let limits = DecryptLimits {
+        max_scrypt_memory: 64 * 1024 * 1024,
+        ..Default::default()
+    };
+    assert!(encrypted.decrypt_pkcs8_with(PASSPHRASE, &limits).is_ok());
+}
+
+#[test]
+fn encrypt_like_openssl() {
+    let plain = read_pem("ec_pkcs8.txt");
+    let options = EncryptOptions::default();
+    let salt = hex::decode("4934C0C45C2BED3398EF277E4BF31E24").unwrap();
+    let iv = hex::decode("AFBE7CCD994377CD2B241FC70AC23869").unwrap();
+    let encrypted = plain
+        .encrypt_pkcs8_with_salt_iv(PASSPHRASE, &options, &salt, &iv)
+        .unwrap();
+    assert_eq!(encrypted, read_pem("ec_pkcs8_aes256_sha256.txt"));
+
+    let options = EncryptOptions {
+        kdf: Kdf::Scrypt {
+            log_n: 14,
+            r: 8,
+            p: 1,
+        },
+        cipher: Cipher::Aes192Cbc,
+    };
+    let salt = hex::decode("EC9A708294564B8D5A7548195831CE38").unwrap();
+    let iv = hex::decode("87F260DF081908B0447BD2D8AC896FEC").unwrap();
+    let encrypted = plain
+        .encrypt_pkcs8_with_salt_iv(PASSPHRASE, &options, &salt, &iv)
+        .unwrap();
+    assert_eq!(encrypted, read_pem("ec_pkcs8_scrypt_aes192.txt"));
+}
+
+#[test]
+fn encrypt_roundtrip() {
+    let plain = read_pem("ec_pkcs8.txt");
+    let ciphers = [
+        Cipher::DesCbc,
+        Cipher::DesEde3Cbc,
+        Cipher::Aes128Cbc,
+        Cipher::Aes192Cbc,
+        Cipher::Aes256Cbc,
+    ];
+    for &cipher in &ciphers {
+        for &prf in &[Prf::HmacSha1, Prf::HmacSha256] {
+            let options = EncryptOptions {
+                kdf: Kdf::Pbkdf2 {
+                    prf,
+                    iterations: 10,
+                },
+                cipher,
+            };
+            let encrypted = plain.encrypt_pkcs8(PASSPHRASE, &options).unwrap();
+            assert_eq!(&encrypted.label, ENC_PRIVKEY_LABEL);
+            assert_eq!(encrypted.decrypt_pkcs8(PASSPHRASE).unwrap(), plain);
+        }
+    }
+}
+
+#[test]
+fn encrypt_wrong_label() {
+    let encrypted = read_pem("ec_pkcs8_des3.txt");
+    assert!(encrypted
+        .encrypt_pkcs8(PASSPHRASE, &EncryptOptions::default())
+        .is_err());
+}
@@ -0,0 +1,105 @@
+extern crate easypem;
+
+mod common;
+
+use common::*;
+use easypem::headers::*;
+use easypem::*;
+use std::path::Path;
+
+/// Base64 lines of a PEM file, as header continuation lines
+fn folded_base64<P: AsRef<Path>>(path: P) -> String {
+    read_asset(path)
+        .lines()
+        .filter(|line| !line.starts_with("-----"))
+        .map(|line| format!("\n {}", line))
+        .collect()
+}
+
+#[test]
+fn crl_message() {
+    let text = format!(
+        "-----BEGIN PRIVACY-ENHANCED MESSAGE-----
+Proc-Type: 4,CRL
+CRL:{}
+Issuer-Certificate:{}
+CRL:{}
+
+-----END PRIVACY-ENHANCED MESSAGE-----",
+        folded_base64("crl.txt"),
+        folded_base64("certificate.txt"),
+        folded_base64("crl.txt"),
+    );
+    let pem = text.parse::<PemMessage>().unwrap();
+    assert!(pem.content.is_empty());
+    assert_eq!(
+        pem.headers.proc_type,
+        Some(ProcType(4, ProcTypeSpecifier::CRL))
+    );
+    let crl = read_pem("crl.txt").content;
+    let cert = read_pem("certificate.txt").content;
+    assert_eq!(
+        pem.headers.crls,
+        vec![
+            CRLInfo {
+                crl: CRL(crl.clone()),
+                originator_certificate: None,
+                issuer_certificate: vec![Certificate(cert)],
+            },
+            CRLInfo {
+                crl: CRL(crl),
+                originator_certificate: None,
+                issuer_certificate: Vec::new(),
+            },
+        ]
+    );
+    assert_eq!(pem.to_string(), text);
+
+    // Typed values are written with the same folding
+    let mut typed = pem.clone();
+    typed.headers.fields.clear();
+    assert_eq!(typed.to_string(), text);
+
+    let stream: Vec<_> = PemReader::new(text.as_bytes()).collect();
+    assert_eq!(stream.len(), 1);
+    assert_eq!(stream[0].as_ref().unwrap(), &pem);
+}
+
+#[test]
+fn crl_retrieval_request() {
+    const REQUEST: &str = "-----BEGIN PRIVACY-ENHANCED MESSAGE-----
+Proc-Type: 4,CRL-RETRIEVAL-REQUEST
+Issuer:
+ MFExCzAJBgNVBAYTAlVTMSAwHgYDVQQKExdSU0EgRGF0YSBTZWN1cml0eSwgSW5j
+ LjEPMA0GA1UECxMGQmV0YSAxMQ8wDQYDVQQLEwZOT1RBUlk=
+Issuer:
+ MFExCzAJBgNVBAYTAlVTMSAwHgYDVQQKExdSU0EgRGF0YSBTZWN1cml0eSwgSW5j
+
+-----END PRIVACY-ENHANCED MESSAGE-----";
+    let pem = REQUEST.parse::<PemMessage>().unwrap();
+    assert_eq!(
+        pem.headers.proc_type,
+        Some(ProcType(4, ProcTypeSpecifier::CRL_RETRIEVAL_REQUEST))
+    );
+    assert_eq!(pem.headers.issuers.len(), 2);
+    assert_eq!(pem.headers.issuers[0].len(), 83);
+    assert_eq!(pem.headers.issuers[1].len(), 48);
+    assert_eq!(pem.to_string(), REQUEST);
+
+    let mut modified = pem.clone();
+    modified.headers.issuers.pop();
+    let text = modified.to_string();
+    assert_eq!(text.matches("Issuer:").count(), 1);
+    assert_eq!(text.parse::<PemMessage>().unwrap().headers.issuers.len(), 1);
+}
+
+#[test]
+fn crl_field_out_of_place() {
+    const MESSAGE: &str = "-----BEGIN PRIVACY-ENHANCED MESSAGE-----
+Proc-Type: 4,CRL
+Issuer-Certificate:
+ MIIB
+
+-----END PRIVACY-ENHANCED MESSAGE-----";
+    assert!(MESSAGE.parse::<PemMessage>().is_err());
+}
@@ -5,7 +5,6 @@ extern crate hex_literal;
 use easypem::*;
 use std::fs;
 use std::path::{Path, PathBuf};
-use std::str::from_utf8;
 
 fn locate_test_files<P: AsRef<Path>>(path: P) -> PathBuf {
     let mut abspath = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
@@ -215,3 +214,131 @@ fn parse_pubkey() {
         ),
     );
 }
+
+const ASSETS: &[&str] = &[
+    "attr_cert.txt",
+    "cert_req.txt",
+    "certificate.txt",
+    "cms.txt",
+    "crl.txt",
+    "enc_privatekey.txt",
+    "pkcs7.txt",
+    "privatekey.txt",
+    "publickey.txt",
+];
+
+fn parse_with(text: &str, strictness: Strictness) -> Option<PemMessage> {
+    PemMessage::parse_with(
+        text,
+        &ParseOptions {
+            strictness,
+            ..Default::default()
+        },
+    )
+    .ok()
+}
+
+#[test]
+fn strictness_accepts_assets() {
+    for asset in ASSETS {
+        let pemtext = fs::read_to_string(locate_test_files(asset)).unwrap();
+        let standard = parse_with(&pemtext, Strictness::Standard).unwrap();
+        assert_eq!(parse_with(&pemtext, Strictness::Strict).unwrap(), standard);
+        assert_eq!(parse_with(&pemtext, Strictness::Lax).unwrap(), standard);
+    }
+}
+
+#[test]
+fn explanatory_text() {
+    let pemtext = fs::read_to_string(locate_test_files("publickey.txt")).unwrap();
+    let explained = format!(
+        "Public-Key: (384 bit)\n    pub:\n        04:9f:52:e5\nASN1 OID: secp384r1\n{}",
+        pemtext
+    );
+
+    let pem = parse_with(&explained, Strictness::Standard).unwrap();
+    assert_eq!(pem.label, PUBKEY_LABEL);
+    assert_eq!(
+        pem.explanatory_text(),
+        "Public-Key: (384 bit)\n    pub:\n        04:9f:52:e5\nASN1 OID: secp384r1\n"
+    );
+    assert_eq!(pem.content, pemtext.parse::<PemMessage>().unwrap().content);
+    assert_eq!(parse_with(&explained, Strictness::Lax).unwrap(), pem);
+    assert!(parse_with(&explained, Strictness::Strict).is_none());
+}
+
+#[test]
+fn strict_rejects() {
+    // Short line in the middle of the content
+    assert!(parse_with(
+        "-----BEGIN MESSAGE-----\nVGhp\ncyBpcyBhIG1lc3NhZ2U=\n-----END MESSAGE-----",
+        Strictness::Strict
+    )
+    .is_none());
+    // Trailing whitespace
+    let trailing = "-----BEGIN MESSAGE-----\nVGhpcyBpcyBhIG1lc3NhZ2U= \n-----END MESSAGE-----";
+    assert!(parse_with(trailing, Strictness::Strict).is_none());
+    assert!(parse_with(trailing, Strictness::Standard).is_some());
+    // Missing padding
+    assert!(parse_with(
+        "-----BEGIN MESSAGE-----\nVGhpcyBpcyBhIG1lc3NhZ2U\n-----END MESSAGE-----",
+        Strictness::Strict
+    )
+    .is_none());
+}
+
+#[test]
+fn lax_whitespace() {
+    let text =
+        "  -----BEGIN MESSAGE-----VGhp\tcyBp\r\n\r\n cyBhIG1l\x0bc3NhZ2U =\n-----END MESSAGE-----";
+    let pem = parse_with(text, Strictness::Lax).unwrap();
+    assert_eq!(pem.content, b"This is a message");
+    assert!(parse_with(text, Strictness::Standard).is_none());
+}
+
+#[test]
+fn typed_labels() {
+    let assets = [
+        ("attr_cert.txt", PemLabel::AttributeCertificate),
+        ("cert_req.txt", PemLabel::CertificateRequest),
+        ("certificate.txt", PemLabel::Certificate),
+        ("cms.txt", PemLabel::Cms),
+        ("crl.txt", PemLabel::X509Crl),
+        ("ec_privatekey.txt", PemLabel::EcPrivateKey),
+        ("enc_privatekey.txt", PemLabel::EncryptedPrivateKey),
+        ("pkcs7.txt", PemLabel::Pkcs7),
+        ("privatekey.txt", PemLabel::PrivateKey),
+        ("publickey.txt", PemLabel::PublicKey),
+    ];
+    for (file, label) in &assets {
+        let pemtext = fs::read_to_string(locate_test_files(file)).unwrap();
+        let pem = pemtext.parse::<PemMessage>().unwrap();
+        assert_eq!(&pem.typed_label(), label, "{}", file);
+        assert_eq!(label.to_string(), pem.label);
+    }
+
+    for label in PemLabel::KNOWN {
+        assert_eq!(&label.as_str().parse::<PemLabel>().unwrap(), label);
+        if label.is_rfc7468() {
+            assert_eq!(&label.preferred(), label);
+        }
+    }
+    let other: PemLabel = "SSH2 PUBLIC KEY".parse().unwrap();
+    match &other {
+        PemLabel::Other(label) => assert_eq!(label.as_str(), "SSH2 PUBLIC KEY"),
+        label => panic!("{:?} is not an other label", label),
+    }
+    assert_eq!(other.preferred(), other);
+
+    let aliases = [
+        ("X509 CERTIFICATE", PemLabel::Certificate),
+        ("X.509 CERTIFICATE", PemLabel::Certificate),
+        ("NEW CERTIFICATE REQUEST", PemLabel::CertificateRequest),
+        ("CRL", PemLabel::X509Crl),
+    ];
+    for (alias, preferred) in &aliases {
+        let label = alias.parse::<PemLabel>().unwrap();
+        assert!(!label.is_rfc7468(), "{}", alias);
+        assert_eq!(&label.preferred(), preferred, "{}", alias);
+    }
+}